@@ -0,0 +1,43 @@
+// Times `Game::perft` on fixed positions, for checking changes to move processing.
+// Run with `cargo bench --bench perft`.
+use starlight_engine::game::{Game, Move};
+use std::time::Instant;
+
+const RUNS: u32 = 5;
+
+fn main() {
+    let selects = [
+        "select large green",
+        "select small blue",
+        "select large yellow",
+        "select medium green",
+        "select medium yellow",
+        "select small red",
+    ];
+    let selects: Vec<Move> = selects.iter().map(|m| m.parse().unwrap()).collect();
+    let opened = Game::from_moves(&selects).unwrap();
+    let sacrifice = Game::from_system_list(
+        "white: g3 b1 | y1 r2 |\nblack: y2 b2 | | g2 r1\nmid: y1 | y3 g1 | b3\nturn: white",
+    )
+    .unwrap();
+
+    let positions = [("setup", Game::new(), 3), ("opened", opened, 9), ("sacrifice", sacrifice, 5)];
+    for (name, game, depth) in positions {
+        // Keep the fastest run, the one least disturbed by the rest of the machine
+        let mut best = f64::INFINITY;
+        let mut nodes = 0;
+        for _ in 0..RUNS {
+            let start = Instant::now();
+            nodes = std::hint::black_box(&game).perft(depth);
+            best = best.min(start.elapsed().as_secs_f64());
+        }
+        println!(
+            "{:<10} depth {}: {:>8} nodes in {:>8.2} ms, {:>10.0} nodes/s",
+            name,
+            depth,
+            nodes,
+            best * 1000.0,
+            nodes as f64 / best
+        );
+    }
+}
@@ -0,0 +1,143 @@
+// Two controllers taking turns on a session, the loop an application actually runs
+use crate::game::{Move, Player, MOVES};
+use crate::session::{Adjudication, Session};
+use rand::rngs::SmallRng;
+#[cfg(test)]
+use rand::SeedableRng;
+
+// Where a human's moves come from, e.g. a terminal prompt. None resigns.
+pub type MoveReader = Box<dyn FnMut(&Session) -> Option<Move> + Send>;
+
+// Whoever chooses a side's moves
+pub enum Controller {
+    Human(MoveReader), // A move the session refuses is asked for again
+    // Plays `Game::ai_move` at `level`, drawing from `rng`, so the same seed replays
+    // the same game
+    Ai { level: u8, rng: SmallRng },
+}
+
+impl Controller {
+    // Method to get the controller's next move, None to resign
+    fn choose(&mut self, session: &Session) -> Option<Move> {
+        match self {
+            Controller::Human(read) => read(session),
+            Controller::Ai { level, rng } => {
+                let pick = session.game().ai_move(*level, rng)?;
+                // A variant rule may veto the engine's pick; fall back to one it allows
+                let legal = session.legal_moves();
+                let i = if legal.contains(&pick) {
+                    pick
+                } else {
+                    *legal.first()?
+                };
+                Some(MOVES[i])
+            }
+        }
+    }
+}
+
+// A game between two controllers, played out on a session
+pub struct Match {
+    session: Session,
+    white: Controller,
+    black: Controller,
+}
+
+impl Match {
+    // Constructor method to pit two controllers against each other from a session,
+    // which carries the variant rules (ply limit, move filter, auto-pass) to play by
+    pub fn new(session: Session, white: Controller, black: Controller) -> Self {
+        Self {
+            session,
+            white,
+            black,
+        }
+    }
+
+    // Method to get the session being played
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    // Method to have the player to move play one move, returning it. None if the game
+    // was already over or the player resigned instead.
+    pub fn step(&mut self) -> Option<Move> {
+        if self.session.adjudicate() != Adjudication::Ongoing {
+            return None;
+        }
+        let player = self.session.game().to_move();
+        let controller = match player {
+            Player::White => &mut self.white,
+            Player::Black => &mut self.black,
+        };
+        loop {
+            let Some(m) = controller.choose(&self.session) else {
+                self.session.resign(player);
+                return None;
+            };
+            if self.session.play(m) {
+                return Some(m);
+            }
+            if let Controller::Ai { .. } = controller {
+                unreachable!("the AI chose a refused move {}", m);
+            }
+        }
+    }
+
+    // Method to play until the session is decided, handing each move and the session
+    // after it to `on_move`, and return the verdict. Only a session with a ply limit
+    // is sure to finish.
+    pub fn run<F: FnMut(Move, &Session)>(&mut self, mut on_move: F) -> Adjudication {
+        while let Some(m) = self.step() {
+            on_move(m, &self.session);
+        }
+        self.session.adjudicate()
+    }
+}
+
+#[test]
+fn test_ai_match() {
+    let new_match = || {
+        let mut session = Session::new();
+        session.set_ply_limit(Some(120));
+        Match::new(
+            session,
+            Controller::Ai { level: 1, rng: SmallRng::seed_from_u64(1) },
+            Controller::Ai { level: 1, rng: SmallRng::seed_from_u64(2) },
+        )
+    };
+    let mut game = new_match();
+    let mut moves = Vec::new();
+    let verdict = game.run(|m, session| {
+        assert!(!session.game().render().is_empty());
+        moves.push(m);
+    });
+    assert_ne!(verdict, Adjudication::Ongoing);
+    assert_eq!(game.session().history(), moves);
+    assert!(game.session().game().ply() <= 120);
+
+    // Nothing moves once the match is over
+    assert_eq!(game.step(), None);
+    assert_eq!(game.session().history().len(), moves.len());
+
+    // The same seeds replay the same game
+    let mut replay = new_match();
+    assert_eq!(replay.run(|_, _| {}), verdict);
+    assert_eq!(replay.session().history(), moves);
+}
+
+#[test]
+fn test_human_controller() {
+    use crate::session::WinReason;
+
+    // White's human fumbles a move during setup, then walks away once play starts
+    let mut script = ["select large green", "select small blue", "attack 3", "select large yellow"]
+        .into_iter()
+        .map(|m| m.parse().unwrap());
+    let white = Controller::Human(Box::new(move |_: &Session| script.next()));
+    let black = Controller::Ai { level: 1, rng: SmallRng::seed_from_u64(1) };
+    let mut game = Match::new(Session::new(), white, black);
+    let verdict = game.run(|_, _| {});
+    assert_eq!(verdict, Adjudication::Win(Player::Black, WinReason::Resignation));
+    assert_eq!(game.session().history().len(), 6);
+}
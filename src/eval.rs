@@ -0,0 +1,839 @@
+// Static evaluation of positions for search
+use crate::util::wyhash64;
+use crate::game::{Game, GameResult, Move, PieceInfo, Player, Size, MOVES, PIECE_COUNT};
+use rand::rngs::SmallRng;
+use rand::Rng;
+use std::time::{Duration, Instant};
+#[cfg(test)]
+use crate::selfplay::self_play;
+#[cfg(test)]
+use rand::SeedableRng;
+
+// Score of a decided game, beyond anything the terms below can add up to
+pub const WIN_SCORE: i32 = 10_000;
+
+// Bonus for having every ability somewhere on the board (see `Game::has_full_spectrum`)
+pub const FULL_SPECTRUM_BONUS: i32 = 1;
+
+// Destinations a side's ships need over the opponent's to be worth one small ship
+// (see `mobility`)
+pub const MOBILITY_DIVISOR: i32 = 4;
+
+// Positions the table behind `Game::ranked_moves` and `Game::ai_move` holds
+pub const SEARCH_CACHE_SIZE: usize = 1 << 12;
+
+// AI level from which `Game::ai_move` always plays the best-scoring move; each level
+// below it lets the AI settle for a move scoring one point less than the best
+pub const AI_EXACT_LEVEL: u8 = 2;
+
+// The terms of `evaluate`, each from the same player's point of view, and their sum
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct EvalBreakdown {
+    pub material: i32,           // See `Game::material_balance`
+    pub ability_access: i32,     // FULL_SPECTRUM_BONUS for all four abilities, less the opponent's
+    pub homeworld_safety: i32,   // The opponent's `homeworld_danger` less the player's
+    pub sacrifice_pressure: i32, // The player's best sacrifice less the opponent's
+    pub mobility: i32,           // The player's mobility less the opponent's, over MOBILITY_DIVISOR
+    pub outcome: i32,            // WIN_SCORE, -WIN_SCORE or 0 once the game is decided, else 0
+    pub total: i32,              // The score `evaluate` returns
+}
+
+impl EvalBreakdown {
+    // Method to fill in `total` from the terms
+    fn summed(self) -> Self {
+        Self {
+            total: self.material
+                + self.ability_access
+                + self.homeworld_safety
+                + self.sacrifice_pressure
+                + self.mobility
+                + self.outcome,
+            ..self
+        }
+    }
+}
+
+// One line per position, for logging what the search saw
+impl std::fmt::Display for EvalBreakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "material {} ability_access {} homeworld_safety {} sacrifice_pressure {} \
+             mobility {} outcome {} total {}",
+            self.material,
+            self.ability_access,
+            self.homeworld_safety,
+            self.sacrifice_pressure,
+            self.mobility,
+            self.outcome,
+            self.total
+        )
+    }
+}
+
+// A move recommended by `Game::suggest`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Suggestion {
+    pub move_idx: usize,
+    pub score: i32,     // The search score, from the mover's point of view
+    pub reason: String, // What the move does for the evaluation, in a few words
+    pub leaf: EvalBreakdown, // The terms at the end of the line the search expects
+}
+
+// Function to score a position from `player`'s point of view. Ships count by size
+// (see `Game::material_balance`), plus a small bonus for access to every ability, how
+// exposed each homeworld is, the best sacrifice each side has on hand and where each
+// fleet can travel. See `EvalBreakdown` for the terms.
+pub fn evaluate(game: &Game, player: Player) -> i32 {
+    evaluate_breakdown(game, player).total
+}
+
+// Score taken off a position for each earlier time it was reached
+pub const REPETITION_PENALTY: i32 = 1;
+
+// Function to score a position as `evaluate` does, less a small penalty for each time
+// it already appears in `history` (position hashes, as `Game::hash` gives them). The
+// rules don't care about repetition; this only steers a searcher toward fresh positions.
+pub fn evaluate_with_history(game: &Game, player: Player, history: &[u64]) -> i32 {
+    let hash = game.hash();
+    let seen = history.iter().filter(|&&h| h == hash).count() as i32;
+    evaluate(game, player) - REPETITION_PENALTY * seen
+}
+
+// Function to score a position as `evaluate` does, keeping the terms apart. Once the
+// game is decided only `outcome` counts.
+pub fn evaluate_breakdown(game: &Game, player: Player) -> EvalBreakdown {
+    let outcome = match game.game_result() {
+        GameResult::Ongoing => None,
+        GameResult::Win(p) | GameResult::Resignation(p) if p == player => Some(WIN_SCORE),
+        GameResult::Win(_) | GameResult::Resignation(_) => Some(-WIN_SCORE),
+        GameResult::Draw | GameResult::AgreedDraw => Some(0),
+    };
+    if let Some(outcome) = outcome {
+        return EvalBreakdown {
+            outcome,
+            ..EvalBreakdown::default()
+        }
+        .summed();
+    }
+    let opponent = match player {
+        Player::White => Player::Black,
+        Player::Black => Player::White,
+    };
+    EvalBreakdown {
+        material: game.material_balance(player),
+        ability_access: FULL_SPECTRUM_BONUS
+            * (game.has_full_spectrum(player) as i32 - game.has_full_spectrum(opponent) as i32),
+        homeworld_safety: homeworld_danger(game, opponent) - homeworld_danger(game, player),
+        sacrifice_pressure: sacrifice_pressure(game, player) - sacrifice_pressure(game, opponent),
+        mobility: (mobility(game, player) - mobility(game, opponent)) / MOBILITY_DIVISOR,
+        outcome: 0,
+        total: 0,
+    }
+    .summed()
+}
+
+impl Game {
+    // Method to score the position from `player`'s point of view term by term, for
+    // seeing why the search likes or dislikes it
+    pub fn explain_eval(&self, player: Player) -> EvalBreakdown {
+        evaluate_breakdown(self, player)
+    }
+
+    // Method to find the best move for the player to move, looking `depth` moves ahead
+    // (at least one), with the terms of the position the line it expects ends on; their
+    // total is the move's score. Positions are scored through `cache`. Ties go to the
+    // earliest move in the move table. None if there's no legal move.
+    pub fn best_move(&self, depth: u8, cache: &mut EvalCache) -> Option<(usize, EvalBreakdown)> {
+        let player = self.to_move();
+        let mut best: Option<(usize, EvalBreakdown)> = None;
+        for i in self.legal_moves() {
+            let mut child = self.clone();
+            child.process_move_idx(i);
+            let leaf = search(&child, player, depth.saturating_sub(1), cache);
+            if best.is_none_or(|(_, b)| leaf.total > b.total) {
+                best = Some((i, leaf));
+            }
+        }
+        best
+    }
+
+    // Method to recommend `best_move`'s move, with the evaluation term the move improves
+    // most as the reason. Positions are scored through `cache`, which can be kept
+    // between calls. None if there's no legal move.
+    pub fn suggest(&self, depth: u8, cache: &mut EvalCache) -> Option<Suggestion> {
+        let (move_idx, leaf) = self.best_move(depth, cache)?;
+        let mut child = self.clone();
+        child.process_move_idx(move_idx);
+        Some(Suggestion {
+            move_idx,
+            score: leaf.total,
+            reason: self.explain(MOVES[move_idx], &child, self.to_move(), cache),
+            leaf,
+        })
+    }
+
+    // Method to score every legal move for the player to move, looking `depth` moves
+    // ahead (at least one), best first. Ties keep move table order, so the first entry
+    // is `suggest`'s move.
+    pub fn ranked_moves(&self, depth: u8) -> Vec<(usize, i32)> {
+        let player = self.to_move();
+        let mut cache = EvalCache::new(SEARCH_CACHE_SIZE);
+        let mut ranked: Vec<(usize, i32)> = self
+            .legal_moves()
+            .into_iter()
+            .map(|i| {
+                let mut child = self.clone();
+                child.process_move_idx(i);
+                (i, search(&child, player, depth.saturating_sub(1), &mut cache).total)
+            })
+            .collect();
+        ranked.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+        ranked
+    }
+
+    // Method to search one move deeper at a time, from one up to `max_depth`, until
+    // `budget` runs out, returning the best move of the deepest search finished with the
+    // terms of the line it expects. The first depth always finishes. Unlike `best_move`,
+    // a tie for the best score is broken by drawing from `rng`, so an rng seeded the same
+    // way picks the same move whenever the same depths finish. None if there's no legal
+    // move.
+    pub fn best_move_timed(
+        &self,
+        budget: Duration,
+        max_depth: u8,
+        cache: &mut EvalCache,
+        rng: &mut SmallRng,
+    ) -> Option<(usize, EvalBreakdown)> {
+        let start = Instant::now();
+        let player = self.to_move();
+        let moves = self.legal_moves();
+        let mut tied = Vec::new();
+        'deepen: for depth in 1..=max_depth.max(1) {
+            let mut scored = Vec::with_capacity(moves.len());
+            for &i in &moves {
+                if depth > 1 && start.elapsed() >= budget {
+                    break 'deepen;
+                }
+                let mut child = self.clone();
+                child.process_move_idx(i);
+                scored.push((i, search(&child, player, depth - 1, cache)));
+            }
+            let top = scored.iter().map(|(_, leaf)| leaf.total).max()?;
+            tied = scored.into_iter().filter(|(_, leaf)| leaf.total == top).collect();
+        }
+        Some(tied[rng.random_range(0..tied.len())])
+    }
+
+    // Method to choose a move for an AI of strength `level`, which searches `level` moves
+    // ahead (at least one). From AI_EXACT_LEVEL up this is `suggest`'s move and `rng` is
+    // left alone. Below it, the AI picks among the moves within AI_EXACT_LEVEL - level of
+    // the best score, drawing from `rng`, so an rng seeded the same way replays the same
+    // game. None if there's no legal move.
+    pub fn ai_move(&self, level: u8, rng: &mut SmallRng) -> Option<usize> {
+        let slack = AI_EXACT_LEVEL.saturating_sub(level) as i32;
+        if slack == 0 {
+            let mut cache = EvalCache::new(SEARCH_CACHE_SIZE);
+            return self.suggest(level, &mut cache).map(|s| s.move_idx);
+        }
+        let scored = self.ranked_moves(level);
+        let best = scored.first()?.1;
+        let near: Vec<usize> = scored
+            .into_iter()
+            .filter(|&(_, score)| score >= best - slack)
+            .map(|(i, _)| i)
+            .collect();
+        Some(near[rng.random_range(0..near.len())])
+    }
+
+    // Method to list (my move, opponent's reply) pairs after which the evaluator scores
+    // the game worse for the player to move than it stood once their own turn was over.
+    // Each side's first move is a legal move from the move table; whatever the turn still
+    // owes after it (a movement's destination, a sacrifice's actions) is played out with
+    // that side's best-scoring choice at each step, so a pair covers two whole turns.
+    // Empty during setup.
+    pub fn two_ply_threats(&self) -> Vec<(usize, usize)> {
+        if !self.setup_options().is_empty() {
+            return Vec::new();
+        }
+        let me = self.to_move();
+        let mut threats = Vec::new();
+        for i in self.legal_moves() {
+            let mut mine = self.clone();
+            mine.process_move_idx(i);
+            finish_turn(&mut mine, me);
+            if mine.is_terminal() {
+                continue;
+            }
+            let settled = evaluate(&mine, me);
+            let opponent = mine.to_move();
+            for j in mine.legal_moves() {
+                let mut theirs = mine.clone();
+                theirs.process_move_idx(j);
+                finish_turn(&mut theirs, opponent);
+                if evaluate(&theirs, me) < settled {
+                    threats.push((i, j));
+                }
+            }
+        }
+        threats
+    }
+
+    // Method to name the term that improves most from this position to `after`
+    fn explain(&self, m: Move, after: &Game, player: Player, cache: &mut EvalCache) -> String {
+        let before = cache.evaluate_breakdown(self, player);
+        let after = cache.evaluate_breakdown(after, player);
+        let gains = [
+            (after.outcome - before.outcome, "wins the game"),
+            (after.material - before.material, "gains material"),
+            (
+                after.sacrifice_pressure - before.sacrifice_pressure,
+                "tips the sacrifice threats its way",
+            ),
+            (after.ability_access - before.ability_access, "gains access to every ability"),
+            (after.homeworld_safety - before.homeworld_safety, "shores up its homeworld"),
+            (after.mobility - before.mobility, "opens up routes for the fleet"),
+        ];
+        let (gain, phrase) = gains
+            .iter()
+            .fold((0, ""), |best, &g| if g.0 > best.0 { g } else { best });
+        match (gain, m) {
+            (0, _) => "keeps the position steady".to_string(),
+            (_, Move::Attack(key)) if phrase == "gains material" => {
+                format!("captures a {} ship", key.size().to_str())
+            }
+            _ => phrase.to_string(),
+        }
+    }
+}
+
+// Function to play out the rest of `player`'s turn, taking the move that scores best
+// for them at each step
+fn finish_turn(game: &mut Game, player: Player) {
+    while game.to_move() == player && !game.is_terminal() {
+        let best = game.legal_moves().into_iter().max_by_key(|&i| {
+            let mut child = game.clone();
+            child.process_move_idx(i);
+            evaluate(&child, player)
+        });
+        match best {
+            Some(i) => game.process_move_idx(i),
+            None => break,
+        };
+    }
+}
+
+// Function to score a position for `player` by trying every line `depth` moves deep,
+// `player` picking the best score on their moves and the opponent the worst. The
+// result is the breakdown of the position the chosen line ends on, whose total is the
+// score. Leaves are scored through `cache`.
+fn search(game: &Game, player: Player, depth: u8, cache: &mut EvalCache) -> EvalBreakdown {
+    if depth == 0 || game.is_terminal() {
+        return cache.evaluate_breakdown(game, player);
+    }
+    let scores = game.legal_moves().into_iter().map(|i| {
+        let mut child = game.clone();
+        child.process_move_idx(i);
+        search(&child, player, depth - 1, cache)
+    });
+    // Earliest line on ties either way
+    let best = if game.to_move() == player {
+        scores.reduce(|a, b| if b.total > a.total { b } else { a })
+    } else {
+        scores.reduce(|a, b| if b.total < a.total { b } else { a })
+    };
+    best.unwrap_or_else(|| cache.evaluate_breakdown(game, player))
+}
+
+// Fixed-capacity table of `evaluate` results, term by term, keyed by position hash and
+// perspective. Each slot holds one entry; a newer position landing on the same slot
+// replaces it.
+pub struct EvalCache {
+    slots: Vec<Option<(u64, EvalBreakdown)>>,
+    lookups: u64,
+    hits: u64,
+}
+
+impl EvalCache {
+    // Constructor method to create a cache with room for `capacity` positions
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            slots: vec![None; capacity.max(1)],
+            lookups: 0,
+            hits: 0,
+        }
+    }
+
+    // Method to evaluate a position, reusing the stored score if it has been seen.
+    // Decided games bypass the table, since their score is read off the result.
+    pub fn evaluate(&mut self, game: &Game, player: Player) -> i32 {
+        self.evaluate_breakdown(game, player).total
+    }
+
+    // Method to evaluate a position term by term, as `evaluate` does
+    pub fn evaluate_breakdown(&mut self, game: &Game, player: Player) -> EvalBreakdown {
+        if game.game_result() != GameResult::Ongoing {
+            return evaluate_breakdown(game, player);
+        }
+        // The position hash covers the side to move; the perspective is mixed in here
+        let key = wyhash64(game.hash(), player as u64);
+        let slot = (key % self.slots.len() as u64) as usize;
+        self.lookups += 1;
+        if let Some((k, v)) = self.slots[slot] {
+            if k == key {
+                self.hits += 1;
+                return v;
+            }
+        }
+        let v = evaluate_breakdown(game, player);
+        self.slots[slot] = Some((key, v));
+        v
+    }
+
+    // Method to forget every stored score and reset the counters
+    pub fn clear(&mut self) {
+        self.slots.fill(None);
+        self.lookups = 0;
+        self.hits = 0;
+    }
+
+    // Method to get the fraction of lookups answered from the table
+    pub fn hit_rate(&self) -> f64 {
+        match self.lookups {
+            0 => 0.0,
+            n => self.hits as f64 / n as f64,
+        }
+    }
+}
+
+// Function to count, over `player`'s ships, the systems on the board each could travel
+// to, whether or not the player can move right now. A fleet parked where every other
+// system shares a star size can go nowhere without discovering a new one.
+fn mobility(game: &Game, player: Player) -> i32 {
+    game.fleet_distribution(player)
+        .into_iter()
+        .map(|(system, ships)| ships as i32 * game.move_destinations(system).len() as i32)
+        .sum()
+}
+
+// Function to count what stands to fall at `player`'s homeworld: each of their pieces
+// there in a color one piece short of a catastrophe (see
+// `Game::catastrophe_vulnerabilities`), and one more when a single ship is all that
+// holds it.
+fn homeworld_danger(game: &Game, player: Player) -> i32 {
+    let Some(home) = game.homeworld(player) else {
+        return 0;
+    };
+    let exposed: i32 = game
+        .catastrophe_vulnerabilities(player)
+        .into_iter()
+        .filter(|&(system, _, _)| system == home)
+        .map(|(_, _, pieces)| pieces as i32)
+        .sum();
+    exposed + (game.homeworld_health(player).ships <= 1) as i32
+}
+
+// Function to estimate the best sacrifice a player has on hand, in actions gained.
+// A sacrifice grants one action per size step, but a large ship is only worth
+// sacrificing if there are enough legal follow-up actions to spend all three; each
+// unspent action is the ship's material thrown away for nothing.
+fn sacrifice_pressure(game: &Game, player: Player) -> i32 {
+    (0..PIECE_COUNT as u8)
+        .filter_map(|key| match game.piece_at(key) {
+            Some(PieceInfo::Ship { size, owner, .. }) if owner == player => {
+                let spent = game.sacrifice_yield(key)? as i32;
+                Some(spent - (size_value(size) - spent))
+            }
+            _ => None,
+        })
+        .max()
+        .map_or(0, |v| v.max(0)) // Not sacrificing at all is always an option
+}
+
+fn size_value(size: Size) -> i32 {
+    size as i32 + 1
+}
+
+#[test]
+fn test_mobility() {
+    // Black's ships sit out at small stars; White's home is large and either medium,
+    // reaching all three colonies, or small, reaching none of them
+    let position = |white_home: &str| {
+        let position = format!(
+            "white: {white_home} | y2 r2 g1 |\nblack: b3 y1 | | g2\n\
+             c1: r1 | | y1\nc2: g1 | | b1\nc3: y1 | | r1\nturn: white"
+        );
+        Game::from_system_list(&position).unwrap()
+    };
+    let (open, blocked) = (position("g3 b2"), position("g3 b1"));
+    assert_eq!(mobility(&open, Player::White), 9);
+    assert_eq!(mobility(&open, Player::Black), 3);
+    assert_eq!(mobility(&blocked, Player::White), 0);
+    assert_eq!(mobility(&blocked, Player::Black), 0);
+
+    let (open, blocked) = (
+        evaluate_breakdown(&open, Player::White),
+        evaluate_breakdown(&blocked, Player::White),
+    );
+    assert_eq!(open.mobility, (9 - 3) / MOBILITY_DIVISOR);
+    assert_eq!(blocked.mobility, 0);
+    assert_eq!(open.material, blocked.material);
+    assert!(open.total > blocked.total);
+}
+
+#[test]
+fn test_search_cache() {
+    let game = Game::from_system_list("white: g3 b1 | y3 g1 |\nblack: b2 y1 | | g3 y2\nturn: white")
+        .unwrap();
+    let player = Player::White;
+
+    // The search scores its leaves through the cache, and a warm cache changes nothing
+    let mut cache = EvalCache::new(1024);
+    let cold = search(&game, player, 2, &mut cache);
+    assert_eq!(search(&game, player, 2, &mut cache), cold);
+    assert!(cache.hit_rate() > 0.0);
+    assert_eq!(search(&game, player, 2, &mut EvalCache::new(1)), cold);
+}
+
+#[test]
+fn test_ai_move() {
+    let game = Game::from_system_list(
+        "white: g3 b1 | y3 g1 |\nblack: b2 y1 | | g3 y2\nturn: white",
+    )
+    .unwrap();
+
+    let pick = |level: u8, seed: u64| game.ai_move(level, &mut SmallRng::seed_from_u64(seed));
+
+    // The same seed plays the same move; at the lowest level seeds vary the choice
+    for seed in 0..4 {
+        assert_eq!(pick(1, seed), pick(1, seed));
+    }
+    let picks: std::collections::HashSet<_> = (0..16).filter_map(|s| pick(1, s)).collect();
+    assert!(picks.len() > 1);
+    let legal = game.legal_moves();
+    assert!(picks.iter().all(|i| legal.contains(i)));
+
+    // At the exact level the seed is ignored and the rng untouched
+    let exact = game.suggest(AI_EXACT_LEVEL, &mut EvalCache::new(1024)).unwrap().move_idx;
+    let mut rng = SmallRng::seed_from_u64(0);
+    for _ in 0..4 {
+        assert_eq!(game.ai_move(AI_EXACT_LEVEL, &mut rng), Some(exact));
+    }
+    assert_eq!(rng, SmallRng::seed_from_u64(0));
+}
+
+#[test]
+fn test_best_move_timed() {
+    let game = Game::from_system_list(
+        "white: g3 b1 | y3 g1 |\nblack: b2 y1 | | g3 y2\nturn: white",
+    )
+    .unwrap();
+    let mut cache = EvalCache::new(1024);
+    let timed = |budget: Duration, seed: u64, cache: &mut EvalCache| {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        game.best_move_timed(budget, 2, cache, &mut rng).unwrap()
+    };
+
+    // With time to spare it searches to the cap and scores the way `best_move` does;
+    // the same seed breaks ties the same way and other seeds may not
+    let (_, deep) = game.best_move(2, &mut cache).unwrap();
+    let long = Duration::from_secs(60);
+    let picks: Vec<(usize, EvalBreakdown)> = (0..16).map(|s| timed(long, s, &mut cache)).collect();
+    assert!(picks.iter().all(|&(_, leaf)| leaf.total == deep.total));
+    assert_eq!(timed(long, 3, &mut cache), picks[3]);
+    assert!(picks.iter().any(|&(i, _)| i != picks[0].0));
+
+    // Out of time, it still has the first depth's answer
+    let (_, shallow) = game.best_move(1, &mut cache).unwrap();
+    assert_eq!(timed(Duration::ZERO, 0, &mut cache).1.total, shallow.total);
+}
+
+#[test]
+fn test_ranked_moves() {
+    let game = Game::from_system_list(
+        "white: g3 b1 | y3 g1 |\nblack: b2 y1 | | g3 y2\nturn: white",
+    )
+    .unwrap();
+    for depth in 1..=2 {
+        let ranked = game.ranked_moves(depth);
+        let mut indices: Vec<usize> = ranked.iter().map(|&(i, _)| i).collect();
+        indices.sort();
+        assert_eq!(indices, game.legal_moves());
+        assert!(ranked.windows(2).all(|w| w[0].1 >= w[1].1));
+
+        let suggestion = game.suggest(depth, &mut EvalCache::new(1024)).unwrap();
+        assert_eq!(ranked[0], (suggestion.move_idx, suggestion.score));
+    }
+}
+
+#[test]
+fn test_sacrifice_pressure() {
+    // White keeps a large and a small red ship at home; Black builds small yellows
+    // and parks some of them in White's homeworld as capture targets
+    let play = |arrivals: &[&str]| {
+        let mut game = Game::new();
+        let opening = [
+            "select large green",
+            "select small red",
+            "select large red",
+            "select medium green",
+            "select medium blue",
+            "select large yellow",
+            "construct 6",
+            "construct 15",
+            "pass",
+            "construct 9",
+            "pass",
+            "moveinit 9",
+            "movefinish 24",
+            "pass",
+            "construct 10",
+            "pass",
+        ];
+        for m in opening.iter().chain(arrivals) {
+            assert!(game.process_move(m.parse().unwrap()), "{}", m);
+        }
+        game
+    };
+    let three = play(&["moveinit 10", "movefinish 24", "pass", "moveinit 11", "movefinish 24"]);
+    let one = play(&["pass"]);
+    assert_eq!(three.to_move(), Player::White);
+    assert_eq!(one.to_move(), Player::White);
+    assert_eq!(
+        three.material_balance(Player::White),
+        one.material_balance(Player::White)
+    );
+
+    // The large red can spend all three attacks on three targets; with one target
+    // only the small red's single attack is worth it
+    assert_eq!(sacrifice_pressure(&three, Player::White), 3);
+    assert_eq!(sacrifice_pressure(&one, Player::White), 1);
+    // The yellows leaving Black's home change its safety too, so compare the term alone
+    let term = |game: &Game| evaluate_breakdown(game, Player::White).sacrifice_pressure;
+    assert!(term(&three) > term(&one));
+}
+
+#[test]
+fn test_eval_cache() {
+    let positions: Vec<Game> = (0..40)
+        .map(|seed| crate::fuzz::apply_random_game(seed, 8 + seed as usize % 12))
+        .collect();
+    let mut cache = EvalCache::new(1024);
+    assert_eq!(cache.hit_rate(), 0.0);
+
+    // Cached scores match fresh ones, for both players, on every pass
+    for _ in 0..2 {
+        for game in positions.iter() {
+            for player in [Player::White, Player::Black] {
+                assert_eq!(cache.evaluate(game, player), evaluate(game, player));
+            }
+        }
+    }
+    assert!(cache.hit_rate() > 0.0);
+
+    cache.clear();
+    assert_eq!(cache.hit_rate(), 0.0);
+    assert_eq!(
+        cache.evaluate(&positions[0], Player::White),
+        evaluate(&positions[0], Player::White)
+    );
+}
+
+#[test]
+fn test_suggest() {
+    // White's red ships sit at home with one of Black's two small yellows in reach
+    let mut game = Game::new();
+    for m in [
+        "select large green",
+        "select small red",
+        "select large red",
+        "select medium green",
+        "select medium blue",
+        "select large yellow",
+        "construct 6",
+        "construct 15",
+        "pass",
+        "construct 9",
+        "pass",
+        "moveinit 9",
+        "movefinish 24",
+    ] {
+        assert!(game.process_move(m.parse().unwrap()), "{}", m);
+    }
+    let mut cache = EvalCache::new(1024);
+    let suggestion = game.suggest(1, &mut cache).unwrap();
+    assert_eq!(MOVES[suggestion.move_idx], "attack 9".parse().unwrap());
+    assert_eq!(suggestion.reason, "captures a small ship");
+    let mut after = game.clone();
+    assert!(after.process_move_idx(suggestion.move_idx));
+    assert_eq!(suggestion.score, evaluate(&after, Player::White));
+
+    // Asking again reuses the scores in the cache
+    let before = cache.hit_rate();
+    assert_eq!(game.suggest(1, &mut cache).as_ref(), Some(&suggestion));
+    assert!(cache.hit_rate() > before);
+
+    // Looking a move further, sacrificing the small red makes the same capture and takes
+    // White's home off the brink of a red catastrophe
+    let deeper = game.suggest(2, &mut cache).unwrap();
+    assert_eq!(MOVES[deeper.move_idx], "sacrifice 1".parse().unwrap());
+    assert_eq!(deeper.reason, "shores up its homeworld");
+    assert!(deeper.score > suggestion.score);
+
+    // The terms add up to the plain score
+    let breakdown = evaluate_breakdown(&game, Player::White);
+    assert_eq!(breakdown.total, evaluate(&game, Player::White));
+    assert_eq!(breakdown.outcome, 0);
+}
+
+#[test]
+fn test_homeworld_safety() {
+    // Black's medium green either stays home or sits at White's, where it makes three
+    // greens with White's large star and small ship
+    let safe = Game::from_system_list(
+        "white: g3 b1 | g1 y3 |\nblack: b2 r1 | | y2 g2\nturn: white",
+    )
+    .unwrap();
+    let threatened = Game::from_system_list(
+        "white: g3 b1 | g1 y3 | g2\nblack: b2 r1 | | y2\nturn: white",
+    )
+    .unwrap();
+    assert_eq!(homeworld_danger(&safe, Player::White), 0);
+    assert_eq!(homeworld_danger(&safe, Player::Black), 0);
+    assert_eq!(evaluate_breakdown(&safe, Player::White).homeworld_safety, 0);
+
+    // White's star and ship are one green from going; Black's home has one ship left
+    assert_eq!(homeworld_danger(&threatened, Player::White), 2);
+    assert_eq!(homeworld_danger(&threatened, Player::Black), 1);
+    assert_eq!(evaluate_breakdown(&threatened, Player::White).homeworld_safety, -1);
+    assert_eq!(evaluate_breakdown(&threatened, Player::Black).homeworld_safety, 1);
+}
+
+#[test]
+fn test_explain_eval() {
+    // Every position of a game, from both sides
+    let record = self_play(|game: &Game| game.legal_moves()[0], Some(0), 40);
+    for (game, _, _) in &record {
+        for player in [Player::White, Player::Black] {
+            let b = game.explain_eval(player);
+            let sum = b.material
+                + b.ability_access
+                + b.homeworld_safety
+                + b.sacrifice_pressure
+                + b.mobility
+                + b.outcome;
+            assert_eq!(sum, b.total);
+            assert_eq!(b.total, evaluate(game, player));
+        }
+    }
+
+    // The search reports the position its line ends on
+    let game = &record[record.len() / 2].0;
+    for depth in 1..=2 {
+        let mut cache = EvalCache::new(1024);
+        let suggestion = game.suggest(depth, &mut cache).unwrap();
+        assert_eq!(suggestion.leaf.total, suggestion.score);
+        let best = game.best_move(depth, &mut cache);
+        assert_eq!(best, Some((suggestion.move_idx, suggestion.leaf)));
+    }
+    let b = game.explain_eval(Player::White);
+    assert!(b.to_string().ends_with(&format!("total {}", b.total)));
+}
+
+#[test]
+fn test_two_ply_threats() {
+    // Black's large red guards its homeworld, which White's large yellow can reach
+    let game = Game::from_system_list(
+        "white: g3 b1 | y3 g1 |
+         black: r2 | | r3
+         turn: white",
+    )
+    .unwrap();
+    let index = |m: &str| MOVES.iter().position(|&v| v == m.parse().unwrap()).unwrap();
+
+    // Standing still leaves Black nothing that hurts
+    let threats = game.two_ply_threats();
+    assert!(threats.iter().all(|&(mine, _)| mine != index("pass")));
+
+    // Once the large yellow is under way, landing in Black's homeworld lets Black take it,
+    // while calling the movement off is safe
+    let mut moving = game.clone();
+    assert!(moving.process_move("moveinit 15".parse().unwrap()));
+    assert_eq!(moving.legal_moves(), vec![index("movefinish 3"), index("pass")]);
+    let threats = moving.two_ply_threats();
+    assert!(threats.contains(&(index("movefinish 3"), index("attack 15"))));
+    assert!(threats.iter().all(|&(mine, _)| mine != index("pass")));
+    assert!(Game::new().two_ply_threats().is_empty());
+}
+
+#[test]
+fn test_evaluate_with_history() {
+    // Greedy one-move lookahead, earliest move on ties, scored with or without history
+    fn greedy(game: &Game, history: Option<&[u64]>) -> usize {
+        let me = game.to_move();
+        let score = |i: usize| {
+            let mut child = game.clone();
+            child.process_move_idx(i);
+            match history {
+                Some(history) => evaluate_with_history(&child, me, history),
+                None => evaluate(&child, me),
+            }
+        };
+        let moves = game.legal_moves();
+        let best = moves.iter().map(|&i| score(i)).max().unwrap();
+        moves.into_iter().find(|&i| score(i) == best).unwrap()
+    }
+
+    // Without the penalty both sides shuffle back and forth until the cap
+    let plain = self_play(|game: &Game| greedy(game, None), Some(0), 60);
+    assert_eq!(plain.len(), 60);
+    assert_eq!(plain[0].2, GameResult::Ongoing);
+
+    // Remembering the positions played makes someone break the loop and the game ends
+    let mut seen = Vec::new();
+    let aware = self_play(
+        |game: &Game| {
+            seen.push(game.hash());
+            greedy(game, Some(&seen))
+        },
+        Some(0),
+        60,
+    );
+    assert!(aware.len() < 60);
+    assert_ne!(aware[0].2, GameResult::Ongoing);
+
+    // Each earlier visit costs the same small amount
+    let game = &plain[10].0;
+    let hash = game.hash();
+    let base = evaluate(game, Player::White);
+    assert_eq!(evaluate_with_history(game, Player::White, &[]), base);
+    assert_eq!(
+        evaluate_with_history(game, Player::White, &[hash, 0, hash]),
+        base - 2 * REPETITION_PENALTY
+    );
+}
+
+#[test]
+fn test_suggest_tie_break() {
+    // Copying either of White's ships scores the same, Black's fleet mirroring White's
+    let game = Game::from_system_list(
+        "white: g3 b1 | y3 r1 |\nblack: b2 y1 | | r3 y2\nturn: white",
+    )
+    .unwrap();
+    let [first, second] = ["construct 0", "construct 15"].map(|m| {
+        let i = MOVES.iter().position(|&n| n == m.parse().unwrap()).unwrap();
+        let mut child = game.clone();
+        assert!(child.process_move_idx(i));
+        (i, search(&child, Player::White, 0, &mut EvalCache::new(1)).total)
+    });
+    assert!(first.0 < second.0);
+    assert_eq!(first.1, second.1);
+
+    // The earlier move in the table wins, every time
+    let mut cache = EvalCache::new(1024);
+    for _ in 0..3 {
+        let suggestion = game.suggest(1, &mut cache).unwrap();
+        assert_eq!(suggestion.move_idx, first.0);
+        assert_eq!(suggestion.score, first.1);
+    }
+}
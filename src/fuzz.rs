@@ -0,0 +1,55 @@
+// Random playouts for shaking panics and broken links out of the rules engine
+use crate::util::wyhash64;
+use crate::game::{Game, GameResult, Move, MoveKind, MOVES};
+
+// Function to play up to `max_moves` random legal moves from the start, stopping
+// early once the game is decided. Every move is checked against the board
+// invariants, so this can serve directly as a fuzz target.
+pub fn apply_random_game(seed: u64, max_moves: usize) -> Game {
+    apply_rolls((0..max_moves as u64).map(|step| wyhash64(seed, step)))
+}
+
+// Function to play a random game with each move picked by the next roll, stopping
+// early once the game is decided, checking every move against the board invariants
+fn apply_rolls(rolls: impl IntoIterator<Item = u64>) -> Game {
+    let mut game = Game::new();
+    for (step, roll) in rolls.into_iter().enumerate() {
+        if game.game_result() != GameResult::Ongoing {
+            break;
+        }
+        let m = random_move(&game, roll);
+        assert!(game.process_move(m), "step {}: {}", step, m);
+        assert_eq!(game.check_invariants(), Ok(()), "step {}: {}", step, m);
+    }
+    game
+}
+
+// Function to pick a move for a random playout. Passing is legal whenever setup is
+// over, so it is held back (it would forfeit any sacrifice or movement under way)
+// and only played when nothing else is, or on a rare roll.
+fn random_move(game: &Game, roll: u64) -> Move {
+    let setup = game.setup_options();
+    if !setup.is_empty() {
+        return setup[(roll % setup.len() as u64) as usize];
+    }
+    let moves: Vec<Move> = game
+        .legal_moves()
+        .into_iter()
+        .map(|i| MOVES[i])
+        .filter(|m| m.kind() != MoveKind::Pass)
+        .collect();
+    if moves.is_empty() || roll >> 60 == 0 {
+        return Move::Pass;
+    }
+    moves[(roll % moves.len() as u64) as usize]
+}
+
+// The rolls are generated rather than the seed, so a failing game shrinks to a short one
+#[cfg(test)]
+proptest::proptest! {
+    #![proptest_config(proptest::prelude::ProptestConfig::with_cases(2000))]
+    #[test]
+    fn test_random_games(rolls in proptest::collection::vec(proptest::num::u64::ANY, 0..200)) {
+        apply_rolls(rolls);
+    }
+}
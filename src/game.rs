@@ -0,0 +1,6545 @@
+// Importing necessary libraries and modules
+use arrayvec::ArrayVec;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crate::util::wyhash64;
+use crate::wire::{self, MoveData};
+#[cfg(test)]
+use crate::fuzz::apply_random_game;
+
+// Constants
+pub const COLOR_COUNT: usize = 4; // Red, yellow, green, blue
+pub const SIZE_COUNT: usize = 3; // Small, medium, large
+pub const COPY_COUNT: usize = 3; // Identical copies of each piece in the stash
+pub const PIECE_COUNT: usize = COLOR_COUNT * SIZE_COUNT * COPY_COUNT; // Pieces on the board
+// Total number of possible moves (the 338th output is the value head): attack, construct,
+// sacrifice, both halves of a movement and catastrophe per piece, a transform per piece
+// and other color, a selection per size and color, and pass
+pub const MOVE_COUNT: usize =
+    PIECE_COUNT * (6 + COLOR_COUNT - 1) + SIZE_COUNT * COLOR_COUNT + 1;
+
+// Struct to represent a key (unique identifier for pieces)
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize)]
+pub struct Key(u8);
+
+// Implementation for Key structure
+impl Key {
+    // Function to get the size of a piece associated with a key
+    pub(crate) fn size(self) -> Size {
+        let within_color = self.0 % (SIZE_COUNT * COPY_COUNT) as u8;
+        unsafe { std::mem::transmute(within_color / COPY_COUNT as u8) }
+    }
+
+    // Function to get the color of a piece associated with a key
+    fn color(self) -> Color {
+        unsafe { std::mem::transmute(self.0 / (SIZE_COUNT * COPY_COUNT) as u8) }
+    }
+}
+
+// Function to build the key of one copy (`index` 0 to 2) of a piece, for naming
+// pieces in moves without relying on the encoding. None if there's no such copy.
+pub fn make_key(color: Color, size: Size, index: u8) -> Option<Key> {
+    (index < COPY_COUNT as u8).then(|| {
+        Key((color as usize * SIZE_COUNT * COPY_COUNT + size as usize * COPY_COUNT) as u8 + index)
+    })
+}
+
+// Function to split a key into the color, size and copy index `make_key` takes
+pub fn key_parts(key: Key) -> (Color, Size, u8) {
+    (key.color(), key.size(), key.0 % COPY_COUNT as u8)
+}
+
+// Struct to name a system by its first star, kept apart from `Key` so a ship's slot
+// can't be passed where a system is expected
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize)]
+pub struct SystemId(Key);
+
+impl SystemId {
+    // Constructor method to name the system whose first star has key `star`, as given
+    // by `piece_at`. None if no piece has that key; whether a system is there depends on
+    // the game.
+    pub fn new(star: u8) -> Option<SystemId> {
+        (star < PIECE_COUNT as u8).then_some(SystemId(Key(star)))
+    }
+
+    // Function to get the key of the system's first star, as accepted by `piece_at`
+    pub fn star(self) -> u8 {
+        self.0 .0
+    }
+}
+
+// Enumerations for Size and Color
+#[derive(Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Hash, Debug)]
+#[repr(u8)]
+pub enum Size {
+    Small = 0,
+    Medium = 1,
+    Large = 2,
+}
+
+impl Size {
+    // Function to convert Size enum to a string
+    pub(crate) fn to_str(self) -> &'static str {
+        match self {
+            Size::Small => "small",
+            Size::Medium => "medium",
+            Size::Large => "large",
+        }
+    }
+
+    // Function to create a Size enum from a string
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s.to_lowercase().as_str() {
+            "small" => Ok(Size::Small),
+            "medium" => Ok(Size::Medium),
+            "large" => Ok(Size::Large),
+            _ => Err(()),
+        }
+    }
+
+    // Function to list available Sizes
+    fn list() -> [Size; SIZE_COUNT] {
+        [Size::Small, Size::Medium, Size::Large]
+    }
+
+    // Function to get the number of turns to sacrifice based on size
+    fn sacrifice_turns(self) -> u8 {
+        self as u8 + 1
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum Color {
+    Red = 0,
+    Yellow = 1,
+    Green = 2,
+    Blue = 3,
+}
+
+impl Color {
+    // Function to convert Color enum to a string
+    fn to_str(self) -> &'static str {
+        match self {
+            Color::Red => "red",
+            Color::Yellow => "yellow",
+            Color::Green => "green",
+            Color::Blue => "blue",
+        }
+    }
+
+    // Function to create a Color enum from a string
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s.to_lowercase().as_str() {
+            "red" => Ok(Color::Red),
+            "yellow" => Ok(Color::Yellow),
+            "green" => Ok(Color::Green),
+            "blue" => Ok(Color::Blue),
+            _ => Err(()),
+        }
+    }
+
+    // Function to retrieve the list of available colors
+    fn list() -> [Color; COLOR_COUNT] {
+        [Color::Red, Color::Yellow, Color::Green, Color::Blue]
+    }
+}
+
+// Define the possible moves in the game
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[allow(clippy::enum_variant_names)]
+pub enum Move {
+    Attack(Key),
+    Construct(Key),
+    Transform(Key, Color),
+    Sacrifice(Key),
+    MoveInit(Key),
+    MoveFinish(Key),
+    Select(Size, Color),
+    Catastrophe(Key),
+    Pass,
+}
+
+// The verb of a move, ignoring its target
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum MoveKind {
+    Attack,
+    Construct,
+    Transform,
+    Sacrifice,
+    Move,
+    Select,
+    Catastrophe,
+    Pass,
+}
+
+impl MoveKind {
+    // Function to list every verb, in move table order
+    pub fn list() -> [MoveKind; 8] {
+        [
+            MoveKind::Attack,
+            MoveKind::Construct,
+            MoveKind::Transform,
+            MoveKind::Sacrifice,
+            MoveKind::Move,
+            MoveKind::Select,
+            MoveKind::Catastrophe,
+            MoveKind::Pass,
+        ]
+    }
+}
+
+impl Move {
+    // Function to find the move's position in the move table, if it has one
+    fn index(self) -> Option<usize> {
+        MOVES.iter().position(|&m| m == self)
+    }
+
+    // Function to get the verb of a move (both halves of a movement are `MoveKind::Move`)
+    pub fn kind(self) -> MoveKind {
+        match self {
+            Move::Attack(_) => MoveKind::Attack,
+            Move::Construct(_) => MoveKind::Construct,
+            Move::Transform(..) => MoveKind::Transform,
+            Move::Sacrifice(_) => MoveKind::Sacrifice,
+            Move::MoveInit(_) | Move::MoveFinish(_) => MoveKind::Move,
+            Move::Select(..) => MoveKind::Select,
+            Move::Catastrophe(_) => MoveKind::Catastrophe,
+            Move::Pass => MoveKind::Pass,
+        }
+    }
+}
+
+impl std::fmt::Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Move::Attack(key) => write!(f, "attack {}", key.0),
+            Move::Construct(key) => write!(f, "construct {}", key.0),
+            Move::Transform(key, color) => write!(f, "transform {} {}", key.0, color.to_str()),
+            Move::Sacrifice(key) => write!(f, "sacrifice {}", key.0),
+            Move::MoveInit(key) => write!(f, "moveinit {}", key.0),
+            Move::MoveFinish(key) => write!(f, "movefinish {}", key.0),
+            Move::Select(size, color) => write!(f, "select {} {}", size.to_str(), color.to_str()),
+            Move::Catastrophe(key) => write!(f, "catastrophe {}", key.0),
+            Move::Pass => write!(f, "pass"),
+        }
+    }
+}
+
+// Moves serialize as the text `Display` writes, which `FromStr` reads back
+impl Serialize for Move {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl FromStr for Move {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        match parts.as_slice() {
+            ["attack", key] => Ok(Move::Attack(Key(key.parse().or(Err(()))?))),
+            ["construct", key] => Ok(Move::Construct(Key(key.parse().or(Err(()))?))),
+            ["transform", key, color] => {
+                let color = Color::from_str(color)?;
+                Ok(Move::Transform(Key(key.parse().or(Err(()))?), color))
+            }
+            ["sacrifice", key] => Ok(Move::Sacrifice(Key(key.parse().or(Err(()))?))),
+            ["moveinit", key] => Ok(Move::MoveInit(Key(key.parse().or(Err(()))?))),
+            ["movefinish", key] => Ok(Move::MoveFinish(Key(key.parse().or(Err(()))?))),
+            ["select", size, color] => {
+                let size = Size::from_str(size)?;
+                let color = Color::from_str(color)?;
+                Ok(Move::Select(size, color))
+            }
+            ["catastrophe", key] => Ok(Move::Catastrophe(Key(key.parse().or(Err(()))?))),
+            ["pass"] => Ok(Move::Pass),
+            _ => Err(()),
+        }
+    }
+}
+
+// Field values marking the missing half of a movement in the wire format
+const WIRE_NO_PIECE: u8 = 0b11_1111;
+const WIRE_NO_SYSTEM: u8 = 0b111_1111;
+
+// Function to pack a move into the two-byte wire format shared with `wire::Move`.
+// The wire format describes a movement as one ship and one system, so the two
+// halves of a movement each leave the other field unset.
+pub fn encode_move(m: Move) -> u16 {
+    let data = match m {
+        Move::Attack(key) => MoveData::Attack { piece: key.0 },
+        Move::Construct(key) => MoveData::Construct { piece: key.0 },
+        Move::Transform(key, color) => MoveData::Transform {
+            piece: key.0,
+            color: wire::Color::from_index(color as u8).unwrap(),
+        },
+        Move::Sacrifice(key) => MoveData::Sacrifice { piece: key.0 },
+        Move::MoveInit(key) => MoveData::Move {
+            piece: key.0,
+            system: WIRE_NO_SYSTEM,
+        },
+        Move::MoveFinish(key) => MoveData::Move {
+            piece: WIRE_NO_PIECE,
+            system: key.0,
+        },
+        Move::Select(size, color) => MoveData::Select {
+            size: wire::Size::from_u8(size as u8).unwrap(),
+            color: wire::Color::from_index(color as u8).unwrap(),
+        },
+        Move::Catastrophe(key) => MoveData::Catastrophe { piece: key.0 },
+        Move::Pass => MoveData::Pass,
+    };
+    wire::Move::new(data).bits()
+}
+
+// Function to unpack a move from the wire format, rejecting any bit pattern that
+// `encode_move` can't produce
+pub fn decode_move(v: u16) -> Option<Move> {
+    let key = |v: u8| (v < PIECE_COUNT as u8).then_some(Key(v));
+    let color = |c: wire::Color| Color::list()[c.index() as usize];
+    Some(match wire::Move::try_from_bits(v)? {
+        MoveData::Attack { piece } => Move::Attack(key(piece)?),
+        MoveData::Construct { piece } => Move::Construct(key(piece)?),
+        MoveData::Transform { piece, color: c } => Move::Transform(key(piece)?, color(c)),
+        MoveData::Sacrifice { piece } => Move::Sacrifice(key(piece)?),
+        MoveData::Move { piece, system } => match (piece, system) {
+            (piece, WIRE_NO_SYSTEM) => Move::MoveInit(key(piece)?),
+            (WIRE_NO_PIECE, system) => Move::MoveFinish(key(system)?),
+            _ => return None, // Whole movements have no single `Move`
+        },
+        MoveData::Select { size, color: c } => {
+            Move::Select(Size::list()[size as usize], color(c))
+        }
+        MoveData::Catastrophe { piece } => Move::Catastrophe(key(piece)?),
+        MoveData::Pass => Move::Pass,
+    })
+}
+
+impl TryFrom<u16> for Move {
+    type Error = WireError;
+
+    fn try_from(v: u16) -> Result<Self, Self::Error> {
+        decode_move(v).ok_or(WireError::BadEncoding(v))
+    }
+}
+
+impl TryFrom<&str> for Move {
+    type Error = ();
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Move> for u16 {
+    fn from(m: Move) -> Self {
+        encode_move(m)
+    }
+}
+
+// Function to check whether systems with the given star sizes are connected (they
+// share no size)
+fn sizes_connected(a: (Size, Size), b: (Size, Size)) -> bool {
+    a.0 != b.0 && a.0 != b.1 && a.1 != b.0 && a.1 != b.1
+}
+
+// Function to compare two positions slot by slot, as `piece_at` describes them. Only
+// what a player can see counts, so a ship that stays put while its ring is relinked
+// isn't a change.
+pub fn diff(before: &Game, after: &Game) -> BoardDiff {
+    BoardDiff {
+        changes: (0..PIECE_COUNT as u8)
+            .filter_map(|key| {
+                let (old, new) = (before.piece_at(key)?, after.piece_at(key)?);
+                (old != new).then_some((key, old, new))
+            })
+            .collect(),
+    }
+}
+
+// Format version written at the front of every `encode_history` buffer. Bump it when
+// the layout changes, so older readers refuse newer data instead of misreading it.
+pub const HISTORY_VERSION: u8 = 1;
+
+// Why a buffer's version tag was refused
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VersionError {
+    Missing,     // The buffer is empty, so there is no tag to read
+    Unknown(u8), // A version this build doesn't know how to read
+}
+
+// Why `decode_history` refused a byte string
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DecodeError {
+    Version(VersionError),
+    OddLength,                           // A trailing byte without its pair
+    BadMove { index: usize, bits: u16 }, // The move at `index` isn't a wire-format move
+}
+
+// Function to pack a move list into a version byte followed by two big-endian bytes
+// per move
+pub fn encode_history(moves: &[Move]) -> Vec<u8> {
+    let moves = moves.iter().flat_map(|&m| encode_move(m).to_be_bytes());
+    std::iter::once(HISTORY_VERSION).chain(moves).collect()
+}
+
+// Function to unpack a move list written by `encode_history`. The moves are only
+// checked one by one; whether they make a legal game is left to the caller.
+pub fn decode_history(bytes: &[u8]) -> Result<Vec<Move>, DecodeError> {
+    let bytes = match bytes.split_first() {
+        Some((&HISTORY_VERSION, rest)) => rest,
+        Some((&version, _)) => return Err(DecodeError::Version(VersionError::Unknown(version))),
+        None => return Err(DecodeError::Version(VersionError::Missing)),
+    };
+    if !bytes.len().is_multiple_of(2) {
+        return Err(DecodeError::OddLength);
+    }
+    bytes
+        .chunks_exact(2)
+        .enumerate()
+        .map(|(index, pair)| {
+            let bits = u16::from_be_bytes([pair[0], pair[1]]);
+            decode_move(bits).ok_or(DecodeError::BadMove { index, bits })
+        })
+        .collect()
+}
+
+// Lazily initialize an array of all possible moves in the game
+pub(crate) static MOVES: Lazy<[Move; MOVE_COUNT]> = Lazy::new(|| {
+    // Initialize an array to store all possible moves
+    let mut moves = [Move::Pass; MOVE_COUNT];
+    let mut i = 0;
+
+    // Generating all possible moves
+    // attack
+    for key in KeyRange::all() {
+        moves[i] = Move::Attack(key);
+        i += 1;
+    }
+    // construct
+    for key in KeyRange::all() {
+        moves[i] = Move::Construct(key);
+        i += 1;
+    }
+    // transform
+    for key in KeyRange::all() {
+        for color in Color::list() {
+            if color == key.color() {
+                continue;
+            }
+            moves[i] = Move::Transform(key, color);
+            i += 1;
+        }
+    }
+    // sacrifice
+    for key in KeyRange::all() {
+        moves[i] = Move::Sacrifice(key);
+        i += 1;
+    }
+    // move init
+    for key in KeyRange::all() {
+        moves[i] = Move::MoveInit(key);
+        i += 1;
+    }
+    // move finish
+    for key in KeyRange::all() {
+        moves[i] = Move::MoveFinish(key);
+        i += 1;
+    }
+    // selection
+    for size in Size::list() {
+        for color in Color::list() {
+            moves[i] = Move::Select(size, color);
+            i += 1;
+        }
+    }
+    // trigger catastrophe
+    for key in KeyRange::all() {
+        moves[i] = Move::Catastrophe(key);
+        i += 1;
+    }
+    // end turn
+    moves[i] = Move::Pass;
+    i += 1;
+    // Asserting that the total number of generated moves matches the pre-defined count
+    assert!(i == MOVE_COUNT);
+
+    // Return the generated moves
+    moves
+});
+
+// Function to describe the whole action space: for every index `process_move_idx`
+// accepts, the index, the move as `Display` writes it and its verb
+pub fn move_table() -> Vec<(usize, String, MoveKind)> {
+    MOVES
+        .iter()
+        .enumerate()
+        .map(|(i, m)| (i, m.to_string(), m.kind()))
+        .collect()
+}
+
+// The power a color grants, and the one a sacrifice of that color hands out
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum Ability {
+    Attack = 0,
+    Move = 1,
+    Construct = 2,
+    Transform = 3,
+}
+
+impl Ability {
+    fn for_color(color: Color) -> Ability {
+        unsafe { std::mem::transmute::<Color, Ability>(color) }
+    }
+
+    // Function to get the verb an ability grants during a sacrifice
+    fn kind(self) -> MoveKind {
+        match self {
+            Ability::Attack => MoveKind::Attack,
+            Ability::Move => MoveKind::Move,
+            Ability::Construct => MoveKind::Construct,
+            Ability::Transform => MoveKind::Transform,
+        }
+    }
+}
+
+// Enumeration for Players
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Player {
+    White = 0,
+    Black = 1,
+}
+
+// Implementation for Player enumeration
+impl Player {
+    // Function to get the inverse of a player (White -> Black, Black -> White)
+    pub(crate) fn inv(self) -> Self {
+        unsafe { std::mem::transmute((self as u8) ^ 1) }
+    }
+}
+
+// Enumeration for special actions in the game
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Special {
+    None,
+    Star1,
+    Star2,
+    Ship,
+    Sacrifice(u8, Ability),
+}
+
+// Struct to represent a turn in the game
+#[derive(Clone, Copy)]
+struct Turn {
+    player: Player,
+    special: Special,
+}
+
+// Implementation for Turn structure
+impl Turn {
+    // Function to initialize the first turn of the game, with `first` setting up first
+    fn initial(first: Player) -> Self {
+        Self {
+            player: first,
+            special: Special::Star1,
+        }
+    }
+
+    // Function to calculate the next turn in the game. The player who set up first
+    // also moves first once both homeworlds are built.
+    fn next(self, first: Player) -> Self {
+        let (player, special) = match (self.player, self.special) {
+            (p, Special::None) => (p.inv(), Special::None),
+            (p, Special::Star1) => (p, Special::Star2),
+            (p, Special::Star2) => (p, Special::Ship),
+            (p, Special::Ship) if p == first => (p.inv(), Special::Star1),
+            (p, Special::Ship) => (p.inv(), Special::None),
+            (p, Special::Sacrifice(1, _)) => (p.inv(), Special::None),
+            (p, Special::Sacrifice(v, a)) => (p, Special::Sacrifice(v - 1, a)),
+        };
+        Self { player, special }
+    }
+}
+
+// Struct to represent a ship in the game
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Ship {
+    parent: Key,
+    sibling: Key,
+    player: Player,
+}
+
+// Enumeration to represent different types of pieces in the game
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)]
+enum Piece {
+    Bank,
+    Star { child: Key },
+    BinaryFirst { child: KeyMaybe, sibling: KeyMaybe },
+    BinarySecond { sibling: Key },
+    Ship(Ship),
+}
+
+// Struct to represent an optional Key (Some(Key) or None)
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct KeyMaybe(i8);
+
+// Implementation for KeyMaybe structure
+impl KeyMaybe {
+    // Function to create KeyMaybe with a key (Some(Key))
+    fn some(key: Key) -> Self {
+        Self(key.0 as i8)
+    }
+
+    // Function to create KeyMaybe representing None
+    fn none() -> Self {
+        Self(-1)
+    }
+
+    // Function to get the Key from KeyMaybe (if it exists)
+    fn get(self) -> Option<Key> {
+        match self.0 {
+            -1 => None,
+            v => Some(Key(v as u8)),
+        }
+    }
+
+    // Function to check if KeyMaybe represents Some(Key)
+    fn is_some(self) -> bool {
+        self.0 != -1
+    }
+
+    // Function to check if KeyMaybe represents None
+    fn is_none(self) -> bool {
+        self.0 == -1
+    }
+}
+
+// Iterator over sibling ships on the board
+struct SiblingIter<'a> {
+    board: &'a Board,
+    home: Key,
+    next: KeyMaybe,
+}
+
+// Implementation for the SiblingIter iterator
+impl Iterator for SiblingIter<'_> {
+    type Item = (Ship, Key);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.next.get()?;
+        let ship = match self.board[next] {
+            Piece::Ship(ship) => ship,
+            _ => unreachable!(),
+        };
+        self.next = match ship.sibling {
+            v if v == self.home => KeyMaybe::none(),
+            v => KeyMaybe::some(v),
+        };
+        Some((ship, next))
+    }
+}
+
+// Struct to define a range of keys
+struct KeyRange {
+    next: u8,
+    end: u8,
+}
+
+// Implementation for the KeyRange structure
+impl KeyRange {
+    // Function to create a new range of keys from start to end
+    fn new(start: u8, end: u8) -> Self {
+        Self { next: start, end }
+    }
+
+    // Function to create a range of keys with a specific color and size
+    fn with_color_and_size(color: Color, size: Size) -> Self {
+        let start = (color as u8 * (SIZE_COUNT * COPY_COUNT) as u8) + size as u8 * COPY_COUNT as u8;
+        let end = start + COPY_COUNT as u8;
+        Self::new(start, end)
+    }
+
+    // Function to create a range of all keys
+    fn all() -> Self {
+        Self::new(0, PIECE_COUNT as u8)
+    }
+}
+
+// Implementation for KeyRange iterator
+impl Iterator for KeyRange {
+    type Item = Key;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.end {
+            return None;
+        }
+        let v = Some(Key(self.next));
+        self.next += 1;
+        v
+    }
+}
+
+// Struct representing the game board with pieces
+#[derive(Clone, Copy)]
+struct Board {
+    pieces: [Piece; PIECE_COUNT],
+}
+
+// Implementation for the Board structure
+impl Board {
+    // Function to create a new empty board
+    const fn new() -> Self {
+        Self {
+            pieces: [Piece::Bank; PIECE_COUNT],
+        }
+    }
+
+    // Function to create an iterator over sibling ships of a given key. The walk follows
+    // the ring from `start`, so the same board always yields the same order and the last
+    // ship is `start`'s predecessor, which removals rely on. That order records how the
+    // ships arrived, not their keys: a constructed ship is linked in right after its
+    // builder, and a ship that moves in right after the star's child. Anything shown to
+    // users lists ships in key order instead (see `render_for` and `to_system_list`).
+    fn sibling_iter(&self, start: Key) -> SiblingIter<'_> {
+        SiblingIter {
+            board: self,
+            home: start,
+            next: KeyMaybe::some(start),
+        }
+    }
+}
+
+// Implementation for indexing the Board structure
+impl Index<Key> for Board {
+    type Output = Piece;
+
+    fn index(&self, index: Key) -> &Self::Output {
+        &self.pieces[index.0 as usize]
+    }
+}
+
+// Implementation for mutable indexing the Board structure
+impl IndexMut<Key> for Board {
+    fn index_mut(&mut self, index: Key) -> &mut Self::Output {
+        &mut self.pieces[index.0 as usize]
+    }
+}
+
+// Enumeration for the outcome of a game. In JSON the winner, if any, sits beside the result.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+#[serde(tag = "result", content = "winner", rename_all = "snake_case")]
+pub enum GameResult {
+    Ongoing,
+    Win(Player),
+    Draw,                // Both homeworlds fell at once
+    Resignation(Player), // The given player won because their opponent resigned
+    AgreedDraw,          // A draw offer was accepted
+}
+
+// Everything about the piece at one board slot, as seen from outside the engine
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PieceInfo {
+    Bank,
+    Star { size: Size, color: Color },
+    Ship {
+        size: Size,
+        color: Color,
+        owner: Player,
+        system: SystemId,
+    },
+}
+
+// Enumeration of the ways the board's piece graph can be inconsistent
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InvariantError {
+    ShipParentNotStar { ship: Key },       // A ship's parent is not a star or binary first
+    ShipSiblingNotShip { ship: Key },      // A ship's sibling ring reaches a non-ship or another system
+    ShipSiblingCycle { ship: Key },        // A ship's sibling ring never returns to the ship
+    ShipUnreachable { ship: Key },         // A ship is not in the sibling ring of its star's child
+    StarChildNotShip { star: Key },        // A star's child is not a ship of that star
+    BinaryUnpaired { star: Key },          // A binary star's sibling does not point back to it
+    HomeworldNotStar { player: Player },   // A homeworld pointer does not reference a star
+}
+
+// Whether overpopulations collapse on their own when a turn ends
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum CatastropheMode {
+    #[default]
+    Auto,
+    Manual, // Overpopulations stand until someone issues Move::Catastrophe
+}
+
+// What naming a catastrophe costs the player who names it
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum CatastropheCost {
+    #[default]
+    Free,   // Any number may be named at any point of a turn, as in the published rules
+    Action, // Each one spends an action, the turn's own or one from a sacrifice
+}
+
+// Who builds their homeworld first, and so also takes the first turn of play
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SetupOrder {
+    #[default]
+    WhiteFirst,
+    BlackFirst, // For handicaps where White gets to see Black's homeworld before choosing
+}
+
+impl SetupOrder {
+    // Function to get the player who sets up first
+    fn first(self) -> Player {
+        match self {
+            SetupOrder::WhiteFirst => Player::White,
+            SetupOrder::BlackFirst => Player::Black,
+        }
+    }
+}
+
+// Who wins when a single move brings down both homeworlds
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum MutualDestruction {
+    #[default]
+    Draw,       // Nobody wins, as in the published rules
+    MoverLoses, // The player whose move caused it loses, so their opponent wins
+}
+
+// The variant rules a game is played under, standard Homeworlds by default. Every game
+// is two players building binary homeworlds; these are the choices on top of that.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct RuleSet {
+    pub setup_order: SetupOrder,
+    pub catastrophe_mode: CatastropheMode,
+    pub catastrophe_cost: CatastropheCost,
+    pub mutual_destruction: MutualDestruction,
+}
+
+impl RuleSet {
+    // Players in every game the engine runs
+    pub const PLAYERS: u8 = 2;
+
+    // Method to write the rules as the `key=value` words of a system list `rules:` line
+    fn to_system_list(self) -> String {
+        let setup = match self.setup_order {
+            SetupOrder::WhiteFirst => "white-first",
+            SetupOrder::BlackFirst => "black-first",
+        };
+        let mode = match self.catastrophe_mode {
+            CatastropheMode::Auto => "auto",
+            CatastropheMode::Manual => "manual",
+        };
+        let cost = match self.catastrophe_cost {
+            CatastropheCost::Free => "free",
+            CatastropheCost::Action => "action",
+        };
+        let mutual = match self.mutual_destruction {
+            MutualDestruction::Draw => "draw",
+            MutualDestruction::MoverLoses => "mover-loses",
+        };
+        format!("setup={setup} catastrophes={mode} cost={cost} mutual={mutual}")
+    }
+
+    // Method to apply one `key=value` word of a system list `rules:` line, false if unknown
+    fn apply_system_list(&mut self, word: &str) -> bool {
+        match word.split_once('=') {
+            Some(("setup", "white-first")) => self.setup_order = SetupOrder::WhiteFirst,
+            Some(("setup", "black-first")) => self.setup_order = SetupOrder::BlackFirst,
+            Some(("catastrophes", "auto")) => self.catastrophe_mode = CatastropheMode::Auto,
+            Some(("catastrophes", "manual")) => self.catastrophe_mode = CatastropheMode::Manual,
+            Some(("cost", "free")) => self.catastrophe_cost = CatastropheCost::Free,
+            Some(("cost", "action")) => self.catastrophe_cost = CatastropheCost::Action,
+            Some(("mutual", "draw")) => self.mutual_destruction = MutualDestruction::Draw,
+            Some(("mutual", "mover-loses")) => {
+                self.mutual_destruction = MutualDestruction::MoverLoses
+            }
+            _ => return false,
+        }
+        true
+    }
+}
+
+// Why `process_move_checked` refused a move
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum IllegalReason {
+    GameOver,     // The game has already been decided
+    UnknownMove,  // The index is past the end of the move table
+    WrongPhase,   // The verb isn't open in this part of the turn (setup, sacrifice, movement)
+    Rejected,     // The verb is open, but the rules refuse it in this position
+}
+
+// Why `try_apply_wire` refused a move from the wire
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WireError {
+    BadLength(usize),       // Not exactly two bytes
+    BadEncoding(u16),       // Two bytes that `decode_move` doesn't accept
+    GameOver,               // The game has already been decided
+    Illegal(IllegalReason), // A well-formed move the position refuses
+}
+
+// What an accepted move did to the game
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MoveEffects {
+    pub placed: Vec<u8>,    // Keys taken from the bank
+    pub removed: Vec<u8>,   // Keys returned to the bank, catastrophes included
+    pub turn_ended: bool,   // Play passed to the other player
+    pub catastrophes: u32,  // Overpopulations that collapsed, named or at turn end
+    pub result: GameResult, // The result after the move
+}
+
+// Something a spectator sees happen during a move, as `Game::process_move_events`
+// reports it. Keys are board slots as `Game::piece_at` takes them.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GameEvent {
+    MovePlayed {
+        player: Player,
+        #[serde(rename = "move")]
+        played: Move,
+    },
+    PieceCaptured { key: u8, by: Player },
+    // One overpopulation collapsing, with the system it hit and every piece it removed
+    Catastrophe { system: SystemId, color: Color, removed: Vec<u8> },
+    PhaseChanged { phase: GamePhase },
+    GameOver(GameResult),
+}
+
+impl GameEvent {
+    // Method to write the event as a JSON object tagged by `type`, for a web viewer
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("events hold no maps or fallible fields")
+    }
+}
+
+// What `Game::make` changed, for `Game::unmake` to put back: the slots it touched with
+// their old pieces, and the turn state from before. Held inline, so search can make and
+// unmake moves without allocating.
+pub struct Unmake {
+    changes: ArrayVec<(Key, Piece), PIECE_COUNT>,
+    turn: Turn,
+    moving_piece: KeyMaybe,
+    wstar: KeyMaybe,
+    bstar: KeyMaybe,
+    collapsed_by: Option<Player>,
+    ply: u32,
+    catastrophes: u32,
+}
+
+// The board slots whose visible contents differ between two positions, in key order,
+// each with its piece before and after
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct BoardDiff {
+    pub changes: Vec<(u8, PieceInfo, PieceInfo)>,
+}
+
+impl BoardDiff {
+    // Method to check whether the two positions look the same
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+// What a player's view of the board leaves out, for hidden-information variants
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Fog {
+    #[default]
+    Open,              // Everything is visible, as in standard play
+    HideBank,          // Nobody sees how many of each piece the bank holds
+    HideOpponentSizes, // The viewer sees the colors of the opponent's ships but not their sizes
+}
+
+// Rough stage of a game, as `Game::game_phase` judges it
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GamePhase {
+    Setup,   // Homeworlds are still being chosen
+    Opening, // Few pieces have left the bank
+    Midgame,
+    Endgame, // The bank is running low, or one side is down to a couple of ships
+}
+
+// How close a player's homeworld is to falling, as `Game::homeworld_health` reports it
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct HomeworldHealth {
+    pub stars: u8,           // Stars still standing, 0 once the homeworld is gone
+    pub ships: u8,           // The player's own ships there; the game is lost at 0
+    pub overpopulated: bool, // Some color has four or more pieces there, ready for a catastrophe
+    pub threats: u8,         // Opponent ships there sharing a color with a star
+}
+
+// What one player has placed of their homeworld, as `Game::setup_progress` reports it
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct HomeworldSetup {
+    pub star1: Option<(Size, Color)>,
+    pub star2: Option<(Size, Color)>,
+    pub ship: Option<(Size, Color)>,
+}
+
+// Both homeworlds as built so far
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct SetupProgress {
+    pub white: HomeworldSetup,
+    pub black: HomeworldSetup,
+}
+
+// Well-known homeworld pairs, for transcripts that skip the setup selections
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StandardSetup {
+    // Both homeworlds small and large with a large green ship: White y1 b3, Black r1 b3
+    SmallLarge,
+    // Both homeworlds small and medium with a large green ship: White b1 y2, Black y1 b2
+    SmallMedium,
+}
+
+impl StandardSetup {
+    // Method to get the homeworlds the preset builds
+    pub fn progress(self) -> SetupProgress {
+        let homeworld = |star1, star2| HomeworldSetup {
+            star1: Some(star1),
+            star2: Some(star2),
+            ship: Some((Size::Large, Color::Green)),
+        };
+        let (white, black) = match self {
+            StandardSetup::SmallLarge => (
+                homeworld((Size::Small, Color::Yellow), (Size::Large, Color::Blue)),
+                homeworld((Size::Small, Color::Red), (Size::Large, Color::Blue)),
+            ),
+            StandardSetup::SmallMedium => (
+                homeworld((Size::Small, Color::Blue), (Size::Medium, Color::Yellow)),
+                homeworld((Size::Small, Color::Yellow), (Size::Medium, Color::Blue)),
+            ),
+        };
+        SetupProgress { white, black }
+    }
+
+    // Method to get the six selections that build the preset, White's first
+    fn selects(self) -> [Move; 6] {
+        let progress = self.progress();
+        let mut selects = [Move::Pass; 6];
+        for (i, side) in [progress.white, progress.black].into_iter().enumerate() {
+            for (j, piece) in [side.star1, side.star2, side.ship].into_iter().enumerate() {
+                let (size, color) = piece.unwrap();
+                selects[i * 3 + j] = Move::Select(size, color);
+            }
+        }
+        selects
+    }
+}
+
+// Pieces in play up to which a game still counts as an opening
+pub const OPENING_PIECES: usize = 12;
+
+// Pieces left in the bank at or below which a game counts as an endgame
+pub const ENDGAME_BANK: usize = 12;
+
+// Why `Game::from_system_list` refused a position description; lines count from 1
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SystemListError {
+    Syntax { line: usize },      // Not `name: stars | white ships | black ships`, `turn:`, `rules:`
+    BadPiece { line: usize },    // A piece that isn't a color letter followed by a size 1-3
+    BadSystem { line: usize },   // Too few or too many stars, or a colony with no ships
+    OutOfPieces { line: usize }, // More of one piece than the bank holds
+    Homeworld(Player),           // The player's homeworld is missing or described twice
+    NoTurn,                      // No line says who is to move
+}
+
+// A system for `Game::new_from_board`: its stars (up to two for a homeworld, one
+// otherwise) and the ships orbiting them
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct SystemSpec {
+    pub stars: Vec<(Size, Color)>,
+    pub ships: Vec<(Size, Color, Player)>,
+}
+
+impl SystemSpec {
+    // Function to get the `SystemId` each system will have once `Game::new_from_board`
+    // builds it, pieces coming out of the bank lowest copy first. None for a system
+    // with no star, or whose first star the bank has run out of.
+    pub fn ids(systems: &[SystemSpec]) -> Vec<Option<SystemId>> {
+        let mut taken = HashMap::new();
+        let mut take = |size: Size, color: Color| {
+            let copy = taken.entry((size, color)).or_insert(0);
+            *copy += 1;
+            make_key(color, size, *copy - 1)
+        };
+        systems
+            .iter()
+            .map(|spec| {
+                let stars: Vec<Option<Key>> =
+                    spec.stars.iter().map(|&(size, color)| take(size, color)).collect();
+                for &(size, color, _) in &spec.ships {
+                    take(size, color);
+                }
+                stars.first().copied().flatten().map(SystemId)
+            })
+            .collect()
+    }
+}
+
+// Why `Game::new_from_board` refused a position; systems count from 0 in the order given
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SetupError {
+    BadSystem { system: usize },   // Too few or too many stars, or a colony with no ships
+    OutOfPieces { system: usize }, // More of one piece than the bank holds
+    Homeworld(Player),             // The homeworld is none of the systems or names both
+    Invariant(InvariantError),     // The board built doesn't hold together
+}
+
+// What `Game::add_system` found wrong with a system, placed by the caller
+enum SystemFault {
+    BadSystem,
+    OutOfPieces,
+}
+
+// Words needed for a bit per move table index
+const LEGAL_WORDS: usize = MOVE_COUNT.div_ceil(64);
+
+// The legal moves of a position as a bit per move table index, worked out on first
+// request. Cloning yields an empty cache, so a scratch copy whose fields are edited
+// directly never answers with its parent's moves. The cache is atomic so a `Game`
+// can be shared between threads: readers racing to fill it store the same bits, and
+// the flag is only raised once they're all in.
+#[derive(Default)]
+struct LegalMoveCache {
+    filled: AtomicBool,
+    bits: [AtomicU64; LEGAL_WORDS],
+}
+
+impl LegalMoveCache {
+    // Method to get the cached moves, if they've been worked out
+    fn get(&self) -> Option<[u64; LEGAL_WORDS]> {
+        if !self.filled.load(Ordering::Acquire) {
+            return None;
+        }
+        Some(std::array::from_fn(|i| self.bits[i].load(Ordering::Relaxed)))
+    }
+
+    // Method to fill the cache
+    fn set(&self, bits: [u64; LEGAL_WORDS]) {
+        for (slot, word) in self.bits.iter().zip(bits) {
+            slot.store(word, Ordering::Relaxed);
+        }
+        self.filled.store(true, Ordering::Release);
+    }
+}
+
+impl Clone for LegalMoveCache {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+// The positions a within-turn search has settled, and the ones it's still inside. A
+// position reached again while it's being searched gets a fixed answer, and whatever
+// leaned on that answer is only settled once the search is back at that position.
+#[derive(Default)]
+struct SearchMemo {
+    settled: HashMap<u64, bool>,
+    open: HashMap<u64, usize>, // How deep each position being searched sits
+    cut: Option<usize>,        // The shallowest open position the search ran back into
+}
+
+impl SearchMemo {
+    // Method to search the position `hash` with `search`, answering `cycle` for a
+    // position already being searched
+    fn visit(&mut self, hash: u64, cycle: bool, search: impl FnOnce(&mut Self) -> bool) -> bool {
+        if let Some(&value) = self.settled.get(&hash) {
+            return value;
+        }
+        if let Some(&depth) = self.open.get(&hash) {
+            self.cut = Some(self.cut.map_or(depth, |cut| cut.min(depth)));
+            return cycle;
+        }
+        let depth = self.open.len();
+        let outer = self.cut.take();
+        self.open.insert(hash, depth);
+        let value = search(self);
+        self.open.remove(&hash);
+        // An answer that leaned on a shallower position may change once that one is
+        // settled, unless the answer didn't come from the fixed one
+        match self.cut {
+            Some(cut) if cut < depth && value == cycle => {}
+            _ => {
+                self.settled.insert(hash, value);
+                self.cut = None;
+            }
+        }
+        self.cut = match (outer, self.cut) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        value
+    }
+}
+
+#[derive(Clone)]
+pub struct Game {
+    board: Board,
+    turn: Turn,
+    moving_piece: KeyMaybe,
+    wstar: KeyMaybe,
+    bstar: KeyMaybe,
+    ruleset: RuleSet,
+    collapsed_by: Option<Player>, // Whose move brought down both homeworlds at once
+    ply: u32, // Moves accepted so far, every action and pass counting as one
+    catastrophes: u32, // Catastrophes resolved so far, named or forced
+    legal: LegalMoveCache, // Cleared by every public method that takes `&mut self`
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Game {
+    // Constructor method to create a new game instance
+    pub fn new() -> Self {
+        Self::new_with(SetupOrder::default())
+    }
+
+    // Constructor method to create a new game instance with the given setup order
+    pub fn new_with(setup_order: SetupOrder) -> Self {
+        Self::new_with_ruleset(RuleSet {
+            setup_order,
+            ..RuleSet::default()
+        })
+    }
+
+    // Constructor method to create a new game instance under the given variant rules
+    pub fn new_with_ruleset(ruleset: RuleSet) -> Self {
+        // Initialization of game attributes
+        Self {
+            board: Board::new(),            // Initialize the game board
+            turn: Turn::initial(ruleset.setup_order.first()), // Initialize the turn
+            moving_piece: KeyMaybe::none(), // No moving piece initially
+            wstar: KeyMaybe::none(),        // No star for white initially
+            bstar: KeyMaybe::none(),        // No star for black initially
+            ruleset,                        // Which variant is being played
+            collapsed_by: None,             // No homeworld has fallen yet
+            ply: 0,                         // No moves played yet
+            catastrophes: 0,                // Nothing has collapsed yet
+            legal: LegalMoveCache::default(), // Nothing worked out yet
+        }
+    }
+
+    /// Constructor method to play an opening line from the start, stopping at the first
+    /// refused move with its position in `moves`.
+    ///
+    /// ```
+    /// use starlight_engine::game::{Game, GameResult, Move};
+    ///
+    /// let moves: Vec<Move> = [
+    ///     "select large green",
+    ///     "select small blue",
+    ///     "select large yellow",
+    ///     "select medium yellow",
+    ///     "select medium green",
+    ///     "select small yellow",
+    ///     "construct 15",
+    /// ]
+    /// .iter()
+    /// .map(|m| m.parse().unwrap())
+    /// .collect();
+    /// let game = Game::from_moves(&moves).unwrap();
+    /// assert_eq!(game.ply(), 7);
+    /// assert_eq!(game.game_result(), GameResult::Ongoing);
+    ///
+    /// // Nobody can pass before their homeworld is built
+    /// assert!(Game::from_moves(&[Move::Pass]).is_err());
+    /// ```
+    pub fn from_moves(moves: &[Move]) -> Result<Game, (usize, IllegalReason)> {
+        let mut game = Game::new();
+        for (i, &m) in moves.iter().enumerate() {
+            let idx = m.index().ok_or((i, IllegalReason::UnknownMove))?;
+            game.apply_moves(&[idx]).map_err(|(_, reason)| (i, reason))?;
+        }
+        Ok(game)
+    }
+
+    // Constructor method to build a standard pair of homeworlds and play `moves` from
+    // there, stopping at the first refused move with its position in `moves`
+    pub fn from_moves_with_setup(
+        setup: StandardSetup,
+        moves: &[Move],
+    ) -> Result<Game, (usize, IllegalReason)> {
+        let selects = setup.selects();
+        let all: Vec<Move> = selects.iter().chain(moves).copied().collect();
+        // Presets are all legal, so a refused move always lies past them
+        Game::from_moves(&all).map_err(|(i, reason)| (i - selects.len(), reason))
+    }
+
+    // Method to choose whether overpopulations collapse automatically at turn end
+    pub fn set_catastrophe_mode(&mut self, mode: CatastropheMode) {
+        self.ruleset.catastrophe_mode = mode;
+        self.legal = LegalMoveCache::default();
+    }
+
+    // Method to choose whether naming a catastrophe spends an action
+    pub fn set_catastrophe_cost(&mut self, cost: CatastropheCost) {
+        self.ruleset.catastrophe_cost = cost;
+        self.legal = LegalMoveCache::default();
+    }
+
+    // Method to choose who wins when one move brings down both homeworlds
+    pub fn set_mutual_destruction(&mut self, rule: MutualDestruction) {
+        self.ruleset.mutual_destruction = rule;
+        self.legal = LegalMoveCache::default();
+    }
+
+    // Method to get the variant rules the game is played under
+    pub fn ruleset(&self) -> RuleSet {
+        self.ruleset
+    }
+
+    // Method to collapse every standing overpopulation. One pass over the keys is a
+    // fixed point: a catastrophe only returns pieces to the bank, so it can't create
+    // another, and ships keep pointing at whichever star takes their system over.
+    fn force_catastrophes(&mut self) {
+        if self.ruleset.catastrophe_mode == CatastropheMode::Manual {
+            return;
+        }
+        for key in KeyRange::all() {
+            _ = self.process_catastrophe(key);
+        }
+    }
+
+    fn advance(&mut self) {
+        let next_turn = self.turn.next(self.ruleset.setup_order.first());
+        if next_turn.player != self.turn.player {
+            self.hand_over(next_turn);
+        } else {
+            self.turn = next_turn;
+        }
+    }
+
+    // Method to end the current player's turn. This is the only place overpopulations
+    // collapse on their own, so each change of player resolves them exactly once.
+    fn hand_over(&mut self, next_turn: Turn) {
+        debug_assert!(next_turn.player != self.turn.player);
+        self.force_catastrophes();
+        self.turn = next_turn;
+    }
+
+    // Method to attempt an attack on a specific key on the board, as the turn's action or
+    // paid for by the sacrifice under way
+    fn process_attack(&mut self, tkey: Key, is_sacrifice: bool) -> bool {
+        // Check if a piece is already in motion
+        if self.moving_piece.is_some() {
+            return false;
+        }
+
+        // Retrieve ship information at the targeted key
+        let tship = match self.board[tkey] {
+            Piece::Ship(ship) if ship.player != self.turn.player => ship,
+            _ => return false,
+        };
+
+        // Check conditions for potential attack
+        let mut has_color = is_sacrifice || tship.parent.color() == Color::Red;
+        let mut attack_size = None; // The attacker's largest ship here, if any
+
+        for (sship, skey) in self.board.sibling_iter(tkey) {
+            if sship.player != self.turn.player {
+                // Check for invalid sibling ship configurations
+                if skey < tkey && skey.size() == tkey.size() && skey.color() == tkey.color() {
+                    return false;
+                }
+                continue;
+            }
+            // Update conditions for attack based on sibling ships
+            has_color |= skey.color() == Color::Red;
+            attack_size = attack_size.max(Some(skey.size()));
+        }
+
+        // Final validation for a successful attack
+        if !has_color || attack_size < Some(tkey.size()) {
+            return false;
+        }
+
+        // Update the attacked ship's owner and switch turns
+        self.board[tkey] = Piece::Ship(Ship {
+            player: self.turn.player,
+            ..tship
+        });
+        self.advance();
+        true // Attack successful
+    }
+
+    // Method to attempt ship construction on a specific key, as the turn's action or paid
+    // for by the sacrifice under way
+    fn process_construct(&mut self, tkey: Key, is_sacrifice: bool) -> bool {
+        // Check if a piece is already in motion
+        if self.moving_piece.is_some() {
+            return false;
+        }
+
+        // Retrieve ship information at the targeted key
+        let tship = match self.board[tkey] {
+            Piece::Ship(ship) if ship.player == self.turn.player => ship,
+            _ => return false,
+        };
+
+        // Check conditions for potential construction
+        let mut has_color = is_sacrifice || tship.parent.color() == Color::Green;
+        // Check sibling ships and update conditions
+        for (sship, skey) in self.board.sibling_iter(tkey) {
+            if sship.player != self.turn.player {
+                continue;
+            }
+            if skey < tkey && skey.color() == tkey.color() {
+                return false;
+            }
+            has_color |= skey.color() == Color::Green;
+        }
+
+        // Final validation for successful construction
+        if !has_color {
+            return false;
+        }
+
+        // Take the smallest piece of the color left in the bank
+        let nkey = match self.smallest_in_bank(tkey.color()) {
+            Some(v) => v,
+            None => return false,
+        };
+        // 1) tkey -> tkey.next
+        // 2) tkey -> nkey -> tkey.next
+        self.board[tkey] = Piece::Ship(Ship {
+            parent: tship.parent,
+            sibling: nkey,
+            player: self.turn.player,
+        });
+        self.board[nkey] = Piece::Ship(Ship {
+            parent: tship.parent,
+            sibling: tship.sibling,
+            player: self.turn.player,
+        });
+        self.advance();
+        true // Construction successful
+    }
+
+    // Method to attempt ship transformation at a specific key to a given color, as the
+    // turn's action or paid for by the sacrifice under way
+    fn process_transform(&mut self, tkey: Key, tcolor: Color, is_sacrifice: bool) -> bool {
+        // Check if a piece is already in motion
+        if self.moving_piece.is_some() {
+            return false;
+        }
+
+        // Retrieve ship information at the targeted key
+        let tship = match self.board[tkey] {
+            Piece::Ship(ship) if ship.player == self.turn.player => ship,
+            _ => return false,
+        };
+
+        // Check conditions for potential transformation
+        let mut has_color = is_sacrifice || tship.parent.color() == Color::Blue;
+        let mut pkey = tkey;
+        let mut pship = tship;
+        for (sship, skey) in self.board.sibling_iter(tkey) {
+            pkey = skey;
+            pship = sship;
+            if sship.player != self.turn.player {
+                continue;
+            }
+            if skey < tkey && skey.color() == tkey.color() && skey.size() == tkey.size() {
+                return false;
+            }
+            has_color |= skey.color() == Color::Blue;
+        }
+
+        // Final validation for successful transformation
+        if !has_color {
+            return false;
+        }
+
+        // Find an available key for transformation and update the board
+        let nkey = match KeyRange::with_color_and_size(tcolor, tkey.size())
+            .find(|&key| self.board[key] == Piece::Bank)
+        {
+            Some(v) => v,
+            None => return false,
+        };
+        assert!(pship.sibling == tkey);
+        // 1) pkey -> tkey -> tkey.next
+        // 2) pkey -> nkey -> tkey.next
+        // (a lone ship links to itself, so there it becomes nkey -> nkey)
+        let nsibling = if tship.sibling == tkey {
+            nkey
+        } else {
+            tship.sibling
+        };
+        if pkey != tkey {
+            self.board[pkey] = Piece::Ship(Ship {
+                sibling: nkey,
+                ..pship
+            });
+        }
+        self.board[tkey] = Piece::Bank;
+        self.board[nkey] = Piece::Ship(Ship {
+            sibling: nsibling,
+            ..tship
+        });
+        self.replace_child(tship.parent, tkey, nkey);
+        self.advance();
+        true // Transformation successful
+    }
+
+    // Method to repoint a star's child pointer if it references a replaced ship
+    fn replace_child(&mut self, star: Key, old: Key, new: Key) {
+        self.board[star] = match self.board[star] {
+            Piece::Star { child } if child == old => Piece::Star { child: new },
+            Piece::BinaryFirst { child, sibling } if child == KeyMaybe::some(old) => {
+                Piece::BinaryFirst {
+                    child: KeyMaybe::some(new),
+                    sibling,
+                }
+            }
+            piece => piece,
+        };
+    }
+
+    // Method to remove a ship from a key on the board and potentially a star associated with it
+    fn remove_ship_and_maybe_star(
+        &mut self,
+        shkey: Key,      // The key where the ship to be removed is located
+        shprvship: Ship, // The ship prior to the ship to be removed
+        shprvkey: Key,   // The key of the ship prior to the ship to be removed
+        shnxtkey: Key,   // The key of the ship after the ship to be removed
+        stkey: Key,      // The key of the potential star associated with the ship
+    ) {
+        // Remove the ship from the current key on the board
+        self.board[shkey] = Piece::Bank;
+
+        // Check if the ship being removed is the only ship on the star, if it's a non-binary star, forget the star
+        if shkey == shprvkey {
+            // If the ship to be removed is the last ship on the star, handle the star accordingly
+            self.board[stkey] = match self.board[stkey] {
+                // If it's a star, forget it
+                Piece::Star { .. } => Piece::Bank,
+                // If it's a non-binary star, remove the ship association from the star
+                Piece::BinaryFirst { sibling, .. } => Piece::BinaryFirst {
+                    child: KeyMaybe::none(),
+                    sibling,
+                },
+                _ => unreachable!(), // Error case, shouldn't happen
+            };
+        } else {
+            // If the ship being removed is not the only ship on the star
+
+            // Update the sibling ship pointers to bypass the ship being removed
+            self.board[shprvkey] = Piece::Ship(Ship {
+                sibling: shnxtkey, // Update the previous ship's sibling to skip the ship being removed
+                ..shprvship        // Retain other ship attributes from the previous ship
+            });
+
+            // Update the association of the ship with the potential star
+            self.board[stkey] = match self.board[stkey] {
+                // If it's a star, update its child pointer
+                Piece::Star { .. } => Piece::Star { child: shnxtkey },
+                // If it's a non-binary star, update its child pointer and retain sibling information
+                Piece::BinaryFirst { sibling, .. } => Piece::BinaryFirst {
+                    child: KeyMaybe::some(shnxtkey),
+                    sibling,
+                },
+                _ => unreachable!(), // Error case, shouldn't happen
+            };
+        }
+    }
+
+    // Method to attempt a ship sacrifice at a specific key, in place of the turn's action
+    fn process_sacrifice(&mut self, tkey: Key) -> bool {
+        // Check if a piece is already in motion
+        if self.moving_piece.is_some() {
+            return false;
+        }
+
+        // Retrieve ship information at the targeted key
+        let tship = match self.board[tkey] {
+            Piece::Ship(ship) if ship.player == self.turn.player => ship,
+            _ => return false,
+        };
+
+        // Find the parent ship information for the targeted ship
+        let mut pkey = tkey;
+        let mut pship = tship;
+        for (sship, skey) in self.board.sibling_iter(tkey) {
+            pkey = skey;
+            pship = sship;
+            if sship.player != self.turn.player {
+                continue;
+            }
+            if skey < tkey && skey.size() == tkey.size() && skey.color() == tkey.color() {
+                return false;
+            }
+        }
+
+        // Remove target from game
+        self.remove_ship_and_maybe_star(tkey, pship, pkey, tship.sibling, tship.parent);
+        self.turn.special = Special::Sacrifice(
+            tkey.size().sacrifice_turns(),
+            Ability::for_color(tkey.color()),
+        );
+        true // Sacrifice successful
+    }
+
+    // Method to attempt initiating a ship movement at a specific key, as the turn's
+    // action or paid for by the sacrifice under way
+    fn process_move_init(&mut self, tkey: Key, is_sacrifice: bool) -> bool {
+        // Check if a piece is already in motion
+        if self.moving_piece.is_some() {
+            return false;
+        }
+
+        // Retrieve ship information at the targeted key
+        let tship = match self.board[tkey] {
+            Piece::Ship(ship) if ship.player == self.turn.player => ship,
+            _ => return false,
+        };
+
+        // Check conditions for potential movement
+        let mut has_color = is_sacrifice || tship.parent.color() == Color::Yellow;
+        for (sship, skey) in self.board.sibling_iter(tkey) {
+            if sship.player != self.turn.player {
+                continue;
+            }
+            if skey < tkey && skey.size() == tkey.size() && skey.color() == tkey.color() {
+                return false;
+            }
+            has_color |= skey.color() == Color::Yellow;
+        }
+
+        // Final validation for successful movement initiation
+        if !has_color {
+            return false;
+        }
+
+        // Set the moving piece and allow movement
+        self.moving_piece = KeyMaybe::some(tkey);
+        true // Movement initiation successful
+    }
+
+    // Method to find the bank slot a construction of `color` takes: the lowest copy of the
+    // smallest size with one left, None once every piece of the color is in play
+    fn smallest_in_bank(&self, color: Color) -> Option<Key> {
+        Size::list().into_iter().find_map(|size| {
+            KeyRange::with_color_and_size(color, size).find(|&key| self.board[key] == Piece::Bank)
+        })
+    }
+
+    // Method to determine star sizes based on the provided key
+    fn get_star_sizes(&self, tkey: Key) -> (Size, Size) {
+        match self.board[tkey] {
+            Piece::Star { .. } => (tkey.size(), tkey.size()), // If the key is a star, return its size twice
+            Piece::BinaryFirst { sibling, .. } => match sibling.get() {
+                None => (tkey.size(), tkey.size()), // If there's no sibling, return the key's size twice
+                Some(skey) => match self.board[skey] {
+                    Piece::BinarySecond { .. } => (tkey.size(), skey.size()), // If sibling is a BinarySecond, return sizes
+                    _ => unreachable!(), // Unreachable if conditions don't match
+                },
+            },
+            _ => unreachable!(), // Unreachable if conditions don't match
+        }
+    }
+
+    // Method to check whether ships can travel between two systems (they share no star size)
+    fn connected(&self, a: Key, b: Key) -> bool {
+        sizes_connected(self.get_star_sizes(a), self.get_star_sizes(b))
+    }
+
+    // Method to complete a ship movement initiated in try_move_init
+    fn process_move_finish(&mut self, tstar_key: Key) -> bool {
+        // Retrieve the key of the moving piece
+        let fkey = match self.moving_piece.get() {
+            Some(v) => v,
+            None => return false, // If there's no moving piece, exit with failure
+        };
+
+        // Retrieve the child key of the target star for movement
+        let tstar_child_key = match self.board[tstar_key] {
+            Piece::Star { child } => KeyMaybe::some(child),
+            Piece::BinaryFirst { child, .. } => child,
+            _ => return false, // If the target key is not a star or binary first, exit with failure
+        };
+
+        // Retrieve ship information of the moving piece
+        let fship = match self.board[fkey] {
+            Piece::Ship(ship) => ship,
+            _ => unreachable!(), // Unreachable if the moving piece is not a ship
+        };
+        let fstar_key = fship.parent; // Retrieve the parent key of the moving ship
+
+        // Check if ship movement is allowed based on star sizes
+        if !self.connected(fstar_key, tstar_key) {
+            return false; // If sizes match, movement is not allowed, exit with failure
+        }
+
+        // Handle movement and update the board
+        let (pship, pkey) = self.board.sibling_iter(fkey).last().unwrap(); // Retrieve sibling ship info
+        self.remove_ship_and_maybe_star(fkey, pship, pkey, fship.sibling, fstar_key);
+
+        // Update the board based on the existence of a child ship within the target star
+        match tstar_child_key.get() {
+            Some(tckey) => {
+                // If the target star has a child ship
+                let tcship = match self.board[tckey] {
+                    Piece::Ship(ship) => ship, // Retrieve ship information of the target star's child
+                    _ => unreachable!(),       // Unreachable if the target key doesn't hold a ship
+                };
+
+                // Move the moving piece (fkey) to become a sibling of the target star's child
+                self.board[tckey] = Piece::Ship(Ship {
+                    sibling: fkey, // The moving piece becomes a sibling of the target star's child ship
+                    ..tcship       // Maintain other ship attributes from the target star's child
+                });
+
+                // Update the moving piece (fkey) to reflect its new parent and sibling relationships
+                self.board[fkey] = Piece::Ship(Ship {
+                    parent: tcship.parent, // Set the parent of the moving piece as the parent of the target star's child
+                    sibling: tcship.sibling, // Set the sibling of the moving piece based on the target star's child's sibling
+                    player: self.turn.player, // Update the player of the moving piece
+                });
+            }
+            None => {
+                // If the target star doesn't have a child ship
+                self.board[tstar_key] = match self.board[tstar_key] {
+                    // Assign the moving piece as the child ship of the target star
+                    Piece::Star { .. } => Piece::Star {
+                        child: fkey
+                    },
+                    Piece::BinaryFirst { sibling, .. } => Piece::BinaryFirst {
+                        child: KeyMaybe::some(fkey),
+                        sibling,
+                    },
+                    _ => unreachable!(), // Unreachable if the target key isn't of type BinaryFirst
+                };
+
+                // Update the moving piece (fkey) to reflect its new parent and sibling relationships
+                self.board[fkey] = Piece::Ship(Ship {
+                    parent: tstar_key,        // Set the parent of the moving piece as the target star
+                    sibling: fkey,            // Set the sibling of the moving piece to itself
+                    player: self.turn.player, // Update the player of the moving piece
+                });
+            }
+        }
+
+        self.moving_piece = KeyMaybe::none(); // The ship has arrived
+        self.advance(); // Move finished; advance turn
+        true // Successful completion of ship movement
+    }
+    fn star_for(&mut self, player: Player) -> &mut KeyMaybe {
+        match player {
+            Player::White => &mut self.wstar,
+            Player::Black => &mut self.bstar,
+        }
+    }
+    fn star_for_ref(&self, player: Player) -> KeyMaybe {
+        match player {
+            Player::White => self.wstar,
+            Player::Black => self.bstar,
+        }
+    }
+    // Method to attempt piece selection of a specific size and color during setup
+    fn process_select(&mut self, size: Size, color: Color) -> bool {
+        // Find an available key of the specified size and color on the board
+        let tkey = match KeyRange::with_color_and_size(color, size)
+            .find(|&key| self.board[key] == Piece::Bank) // Find an empty slot
+        {
+            Some(v) => v,
+            None => return false, // Exit with failure if the stash is exhausted
+        };
+
+        // Perform selection based on the current special action of the turn
+        match self.turn.special {
+            Special::Star1 => {
+                // Set the board at the chosen key as a BinaryFirst piece with no child or sibling
+                self.board[tkey] = Piece::BinaryFirst {
+                    child: KeyMaybe::none(),
+                    sibling: KeyMaybe::none(),
+                };
+                // Set the star for the respective player to the chosen key
+                *self.star_for(self.turn.player) = KeyMaybe::some(tkey);
+            }
+            Special::Star2 => {
+                // Get the current star key for the respective player
+                let star = self.star_for(self.turn.player).get().unwrap();
+                // Set the board at the chosen key as a BinarySecond piece with the sibling as the current star
+                self.board[tkey] = Piece::BinarySecond { sibling: star };
+                // Set the board at the current star as a BinaryFirst piece with the chosen key as the sibling
+                self.board[star] = Piece::BinaryFirst {
+                    child: KeyMaybe::none(),
+                    sibling: KeyMaybe::some(tkey),
+                };
+            }
+            Special::Ship => {
+                // Get the current star key for the respective player
+                let star = self.star_for(self.turn.player).get().unwrap();
+                // Set the board at the chosen key as a Ship with parent as the star, sibling as the chosen key, and player's turn
+                self.board[tkey] = Piece::Ship(Ship {
+                    parent: star,
+                    sibling: tkey,
+                    player: self.turn.player,
+                });
+                // Set the board at the star as a BinaryFirst piece with the chosen key as the child
+                self.board[star] = match self.board[star] {
+                    Piece::BinaryFirst { sibling, .. } => Piece::BinaryFirst {
+                        child: KeyMaybe::some(tkey),
+                        sibling,
+                    },
+                    _ => unreachable!(), // Unreachable if the star is not in the expected state
+                };
+            }
+            _ => unreachable!(), // Unreachable if the current special action is unexpected
+        }
+
+        self.turn = self.turn.next(self.ruleset.setup_order.first()); // Advance to the next turn
+        true // Successful completion of piece selection
+    }
+
+    // Method to remove a ship, finding its predecessor in the sibling ring
+    fn remove_ship(&mut self, key: Key) {
+        let ship = match self.board[key] {
+            Piece::Ship(ship) => ship,
+            _ => unreachable!(), // Only ships can be removed this way
+        };
+        let (pship, pkey) = self.board.sibling_iter(key).last().unwrap();
+        self.remove_ship_and_maybe_star(key, pship, pkey, ship.sibling, ship.parent);
+    }
+
+    // Method to return a whole system (its stars and every ship in it) to the bank
+    fn remove_system(&mut self, star: Key) {
+        let (child, sibling) = match self.board[star] {
+            Piece::Star { child } => (KeyMaybe::some(child), KeyMaybe::none()),
+            Piece::BinaryFirst { child, sibling } => (child, sibling),
+            _ => unreachable!(), // Systems are always identified by their first star
+        };
+        if let Some(ckey) = child.get() {
+            ArrayVec::<Key, PIECE_COUNT>::from_iter(
+                self.board.sibling_iter(ckey).map(|(_, skey)| skey),
+            )
+            .iter()
+            .for_each(|&skey| self.board[skey] = Piece::Bank);
+        }
+        if let Some(skey) = sibling.get() {
+            self.board[skey] = Piece::Bank;
+        }
+        self.board[star] = Piece::Bank;
+        self.rehome(star, KeyMaybe::none());
+    }
+
+    // Method to repoint any homeworld referencing a star that is going away
+    fn rehome(&mut self, star: Key, new: KeyMaybe) {
+        for home in [&mut self.wstar, &mut self.bstar] {
+            if *home == KeyMaybe::some(star) {
+                *home = new;
+            }
+        }
+    }
+
+    // Method to attempt a catastrophic event at a specific key
+    fn process_catastrophe(&mut self, shkey: Key) -> bool {
+        // Check if a piece is already in motion (it could be swept away mid-flight)
+        if self.moving_piece.is_some() {
+            return false;
+        }
+
+        // Retrieve ship information for the targeted key
+        let shship = match self.board[shkey] {
+            Piece::Ship(ship) => ship,
+            _ => return false, // Exit if the targeted key doesn't hold a ship
+        };
+        let color = shkey.color();
+        let star = shship.parent;
+
+        // Gather the stars of the system that share the ship's color
+        let partner = match self.board[star] {
+            Piece::BinaryFirst { sibling, .. } => sibling.get(),
+            _ => None,
+        };
+        let first_hit = star.color() == color;
+        let second_hit = partner.is_some_and(|v| v.color() == color);
+
+        // Gather the ships of the system that share the ship's color
+        let ships = ArrayVec::<Key, PIECE_COUNT>::from_iter(
+            self.board
+                .sibling_iter(shkey)
+                .map(|(_, skey)| skey)
+                .filter(|skey| skey.color() == color),
+        );
+        // Only the lowest such ship may be named, as they all trigger the same catastrophe
+        if ships.iter().any(|&skey| skey < shkey) {
+            return false;
+        }
+
+        // Check if the conditions for catastrophic removal are not met
+        if ships.len() + (first_hit as usize) + (second_hit as usize) < 4 {
+            return false; // Exit if there are insufficient ships/stars for a catastrophe
+        }
+        self.catastrophes += 1;
+
+        // Remove the ships one at a time so each removal sees the current links
+        for &skey in ships.iter() {
+            self.remove_ship(skey);
+        }
+        if self.board[star] == Piece::Bank {
+            return true; // The last ship left a lone star, which went with it
+        }
+
+        // Remove the stars; a surviving second star takes over the system
+        match (first_hit, partner, second_hit) {
+            (false, _, false) => {}
+            (false, Some(v), true) => {
+                let child = match self.board[star] {
+                    Piece::BinaryFirst { child, .. } => child,
+                    _ => unreachable!(),
+                };
+                self.board[v] = Piece::Bank;
+                self.board[star] = Piece::BinaryFirst {
+                    child,
+                    sibling: KeyMaybe::none(),
+                };
+            }
+            (true, Some(v), false) => {
+                let child = match self.board[star] {
+                    Piece::BinaryFirst { child, .. } => child,
+                    _ => unreachable!(),
+                };
+                if let Some(ckey) = child.get() {
+                    ArrayVec::<Key, PIECE_COUNT>::from_iter(
+                        self.board.sibling_iter(ckey).map(|(_, skey)| skey),
+                    )
+                    .iter()
+                    .for_each(|&skey| {
+                        if let Piece::Ship(ship) = self.board[skey] {
+                            self.board[skey] = Piece::Ship(Ship { parent: v, ..ship });
+                        }
+                    });
+                }
+                self.board[v] = Piece::BinaryFirst {
+                    child,
+                    sibling: KeyMaybe::none(),
+                };
+                self.board[star] = Piece::Bank;
+                self.rehome(star, KeyMaybe::some(v));
+            }
+            _ => self.remove_system(star),
+        }
+
+        true // Successful completion of the catastrophic event
+    }
+
+    fn process_pass(&mut self) -> bool {
+        self.moving_piece = KeyMaybe::none(); // Abandon any half-finished movement
+        self.hand_over(Turn {
+            player: self.turn.player.inv(),
+            special: Special::None,
+        });
+        true
+    }
+
+    // Method to resolve a catastrophe a player named, charging an action for it if the
+    // variant asks for one
+    fn process_named_catastrophe(&mut self, key: Key) -> bool {
+        if self.ruleset.catastrophe_cost == CatastropheCost::Free {
+            return self.process_catastrophe(key);
+        }
+        if !self.process_catastrophe(key) {
+            return false;
+        }
+        self.advance();
+        true
+    }
+
+    pub fn process_move(&mut self, m: Move) -> bool {
+        // A finished game is frozen
+        if self.game_result() != GameResult::Ongoing {
+            return false;
+        }
+        self.legal = LegalMoveCache::default();
+        let player = self.turn.player;
+        // The phase of the turn settles which verbs can apply, so the handlers below
+        // don't check it again
+        let ok = match self.turn.special {
+            // Homeworlds must be built before anything else, passing included
+            Special::Star1 | Special::Star2 | Special::Ship => match m {
+                Move::Select(size, color) => self.process_select(size, color),
+                _ => false,
+            },
+            Special::None => match m {
+                Move::Attack(tkey) => self.process_attack(tkey, false),
+                Move::Construct(tkey) => self.process_construct(tkey, false),
+                Move::Transform(tkey, color) => self.process_transform(tkey, color, false),
+                Move::Sacrifice(tkey) => self.process_sacrifice(tkey),
+                Move::MoveInit(tkey) => self.process_move_init(tkey, false),
+                Move::MoveFinish(tkey) => self.process_move_finish(tkey),
+                Move::Select(..) => false,
+                Move::Catastrophe(tkey) => self.process_named_catastrophe(tkey),
+                Move::Pass => self.process_pass(),
+            },
+            // Each sacrificed action must use the ability the sacrifice granted
+            Special::Sacrifice(_, ability) => match m {
+                Move::Attack(tkey) if ability == Ability::Attack => {
+                    self.process_attack(tkey, true)
+                }
+                Move::Construct(tkey) if ability == Ability::Construct => {
+                    self.process_construct(tkey, true)
+                }
+                Move::Transform(tkey, color) if ability == Ability::Transform => {
+                    self.process_transform(tkey, color, true)
+                }
+                Move::MoveInit(tkey) if ability == Ability::Move => {
+                    self.process_move_init(tkey, true)
+                }
+                Move::MoveFinish(tkey) => self.process_move_finish(tkey),
+                Move::Catastrophe(tkey) => self.process_named_catastrophe(tkey),
+                Move::Pass => self.process_pass(),
+                _ => false,
+            },
+        };
+        // Surface rule bugs at the move that introduced them
+        debug_assert_eq!(self.check_invariants(), Ok(()), "after {}", m);
+        if ok {
+            self.ply += 1;
+        }
+        // Remember whose move it was if both homeworlds have just fallen together
+        if ok
+            && self.game_result() != GameResult::Ongoing
+            && self.homeworld_lost(Player::White)
+            && self.homeworld_lost(Player::Black)
+        {
+            self.collapsed_by = Some(player);
+        }
+        ok
+    }
+
+    // Method to play the move at a move table index. An index past the table is
+    // refused like any illegal move rather than panicking.
+    pub fn process_move_idx(&mut self, i: usize) -> bool {
+        match MOVES.get(i) {
+            Some(&m) => self.process_move(m),
+            None => false,
+        }
+    }
+
+    /// Method to get the game as it would be after a move, leaving this one untouched.
+    /// None if the move is refused.
+    ///
+    /// ```
+    /// use starlight_engine::game::{Game, Move};
+    ///
+    /// let select = |m: &str| m.parse::<Move>().unwrap();
+    /// let game = Game::new();
+    /// let next = game
+    ///     .with_move(select("select large green"))
+    ///     .and_then(|g| g.with_move(select("select small blue")));
+    /// assert_eq!(next.unwrap().ply(), 2);
+    /// assert_eq!(game.ply(), 0);
+    ///
+    /// // Nobody can pass during setup
+    /// assert!(game.with_move(Move::Pass).is_none());
+    /// ```
+    pub fn with_move(&self, m: Move) -> Option<Game> {
+        let mut game = self.clone();
+        game.process_move(m).then_some(game)
+    }
+
+    // Method to list each legal move with the game it leads to. A successor is one move
+    // from the move table, not a whole turn: a sacrifice leads to the game with its
+    // actions still to spend, and starting a movement to the game with the ship in
+    // flight. Overpopulations that collapse on their own at a turn's end are already
+    // resolved in a successor whose move ended the turn.
+    pub fn successors(&self) -> Vec<(Move, Game)> {
+        self.legal_moves()
+            .into_iter()
+            .map(|i| {
+                let mut game = self.clone();
+                game.process_move_idx(i);
+                (MOVES[i], game)
+            })
+            .collect()
+    }
+
+    // Method to count the move sequences of `depth` moves from here, for checking move
+    // generation and timing it. Each step is one move table entry, as in `successors`.
+    pub fn perft(&self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let mut nodes = 0;
+        for i in self.legal_moves() {
+            let mut game = self.clone();
+            game.process_move_idx(i);
+            nodes += game.perft(depth - 1);
+        }
+        nodes
+    }
+
+    // Method to play a move in place, returning what `unmake` needs to take it back.
+    // None if the move is refused, which leaves the game untouched.
+    pub fn make(&mut self, m: Move) -> Option<Unmake> {
+        let before = self.board.pieces;
+        let mut token = Unmake {
+            changes: ArrayVec::new(),
+            turn: self.turn,
+            moving_piece: self.moving_piece,
+            wstar: self.wstar,
+            bstar: self.bstar,
+            collapsed_by: self.collapsed_by,
+            ply: self.ply,
+            catastrophes: self.catastrophes,
+        };
+        if !self.process_move(m) {
+            return None;
+        }
+        for key in KeyRange::all() {
+            if self.board[key] != before[key.0 as usize] {
+                token.changes.push((key, before[key.0 as usize]));
+            }
+        }
+        Some(token)
+    }
+
+    // Method to take back the move `token` came from. Tokens must be unmade in the
+    // reverse order of the moves that made them.
+    pub fn unmake(&mut self, token: Unmake) {
+        for (key, piece) in token.changes {
+            self.board[key] = piece;
+        }
+        self.turn = token.turn;
+        self.moving_piece = token.moving_piece;
+        self.wstar = token.wstar;
+        self.bstar = token.bstar;
+        self.collapsed_by = token.collapsed_by;
+        self.ply = token.ply;
+        self.catastrophes = token.catastrophes;
+        self.legal = LegalMoveCache::default();
+    }
+
+    // Method to apply a move in place, reporting what it did or why it was refused.
+    // A refused move leaves the game untouched.
+    pub fn process_move_checked(&mut self, idx: usize) -> Result<MoveEffects, IllegalReason> {
+        if self.game_result() != GameResult::Ongoing {
+            return Err(IllegalReason::GameOver);
+        }
+        let m = *MOVES.get(idx).ok_or(IllegalReason::UnknownMove)?;
+        if !self.phase_allows(m) {
+            return Err(IllegalReason::WrongPhase);
+        }
+        let before = self.board.pieces;
+        let player = self.turn.player;
+        let catastrophes = self.catastrophes;
+        if !self.process_move(m) {
+            return Err(IllegalReason::Rejected);
+        }
+        let changed = |f: fn(Piece, Piece) -> bool| {
+            KeyRange::all()
+                .filter(|&key| f(before[key.0 as usize], self.board[key]))
+                .map(|key| key.0)
+                .collect()
+        };
+        Ok(MoveEffects {
+            placed: changed(|old, new| old == Piece::Bank && new != Piece::Bank),
+            removed: changed(|old, new| old != Piece::Bank && new == Piece::Bank),
+            turn_ended: self.turn.player != player,
+            catastrophes: self.catastrophes - catastrophes,
+            result: self.game_result(),
+        })
+    }
+
+    // Method to apply a move in place, reporting what a spectator sees happen, in order:
+    // the move, any capture, each collapse (a named catastrophe first, then those at
+    // turn end in the order they resolve), a change of phase and the end of the game.
+    // None if the move is refused, leaving the game untouched.
+    pub fn process_move_events(&mut self, m: Move) -> Option<Vec<GameEvent>> {
+        let player = self.turn.player;
+        let phase = self.game_phase();
+
+        // Replay the move with collapses held back, then resolve them one at a time as
+        // `force_catastrophes` will
+        let mut scratch = self.clone();
+        scratch.ruleset.catastrophe_mode = CatastropheMode::Manual;
+        let named = match m {
+            Move::Catastrophe(key) => match self.board[key] {
+                Piece::Ship(ship) => Some((SystemId(ship.parent), key.color())),
+                _ => None,
+            },
+            _ => None,
+        };
+        let before = scratch.board.pieces;
+        if !self.process_move(m) {
+            return None;
+        }
+        let replayed = scratch.process_move(m);
+        debug_assert!(replayed, "holding back collapses can't make a move illegal");
+        let removed = |before: &[Piece; PIECE_COUNT], after: &Game| -> Vec<u8> {
+            KeyRange::all()
+                .filter(|&key| before[key.0 as usize] != Piece::Bank)
+                .filter(|&key| after.board[key] == Piece::Bank)
+                .map(|key| key.0)
+                .collect()
+        };
+
+        let mut events = vec![GameEvent::MovePlayed { player, played: m }];
+        if let Move::Attack(key) = m {
+            events.push(GameEvent::PieceCaptured { key: key.0, by: player });
+        }
+        if let Some((system, color)) = named {
+            let removed = removed(&before, &scratch);
+            events.push(GameEvent::Catastrophe { system, color, removed });
+        }
+        if scratch.turn.player != player && self.ruleset.catastrophe_mode == CatastropheMode::Auto {
+            for key in KeyRange::all() {
+                let before = scratch.board.pieces;
+                let system = match scratch.board[key] {
+                    Piece::Ship(ship) => SystemId(ship.parent),
+                    _ => continue,
+                };
+                if scratch.process_catastrophe(key) {
+                    let removed = removed(&before, &scratch);
+                    events.push(GameEvent::Catastrophe { system, color: key.color(), removed });
+                }
+            }
+        }
+        debug_assert!(scratch.board.pieces == self.board.pieces);
+        if self.game_phase() != phase {
+            events.push(GameEvent::PhaseChanged { phase: self.game_phase() });
+        }
+        if self.game_result() != GameResult::Ongoing {
+            events.push(GameEvent::GameOver(self.game_result()));
+        }
+        Some(events)
+    }
+
+    // Method to apply a sequence of moves in order, stopping at the first refused one
+    // with its position in `moves`. The game is left as it was after the last good move.
+    pub fn apply_moves(&mut self, moves: &[usize]) -> Result<(), (usize, IllegalReason)> {
+        for (i, &idx) in moves.iter().enumerate() {
+            self.process_move_checked(idx).map_err(|reason| (i, reason))?;
+        }
+        Ok(())
+    }
+
+    // Method to apply a sequence of moves in order, skipping refused ones instead of
+    // stopping, for replaying noisy data. `on_illegal` gets the position in `moves` of
+    // each skipped move; the count of moves applied is returned.
+    pub fn apply_moves_lenient(
+        &mut self,
+        moves: &[usize],
+        mut on_illegal: impl FnMut(usize),
+    ) -> usize {
+        let mut applied = 0;
+        for (i, &idx) in moves.iter().enumerate() {
+            if self.process_move_idx(idx) {
+                applied += 1;
+            } else {
+                on_illegal(i);
+            }
+        }
+        applied
+    }
+
+    // Method to apply one move as two big-endian wire bytes, as `encode_history` writes
+    // them. Meant for untrusted input: any byte string is answered with an error rather
+    // than a panic, and a refused move leaves the game untouched.
+    pub fn try_apply_wire(&mut self, bytes: &[u8]) -> Result<(), WireError> {
+        let &[hi, lo] = bytes else {
+            return Err(WireError::BadLength(bytes.len()));
+        };
+        let bits = u16::from_be_bytes([hi, lo]);
+        let m = decode_move(bits).ok_or(WireError::BadEncoding(bits))?;
+        let idx = m.index().ok_or(WireError::BadEncoding(bits))?;
+        match self.process_move_checked(idx) {
+            Ok(_) => Ok(()),
+            Err(IllegalReason::GameOver) => Err(WireError::GameOver),
+            Err(reason) => Err(WireError::Illegal(reason)),
+        }
+    }
+
+    // Method to check whether the current part of the turn admits a move's verb at all
+    fn phase_allows(&self, m: Move) -> bool {
+        let setup = matches!(
+            self.turn.special,
+            Special::Star1 | Special::Star2 | Special::Ship
+        );
+        let finish = matches!(m, Move::MoveFinish(_));
+        match m.kind() {
+            MoveKind::Pass => !setup,
+            MoveKind::Select => setup,
+            MoveKind::Catastrophe => self.moving_piece.is_none(),
+            _ if setup => false,
+            _ if self.moving_piece.is_some() || finish => self.moving_piece.is_some() && finish,
+            kind => match self.turn.special {
+                Special::Sacrifice(_, ability) => kind == ability.kind(),
+                _ => true,
+            },
+        }
+    }
+
+    // Method to list the indices of all moves that would currently succeed. The list
+    // is kept until the game changes, so asking again for the same position is cheap.
+    pub fn legal_moves(&self) -> Vec<usize> {
+        if let Some(bits) = self.legal.get() {
+            return (0..MOVE_COUNT)
+                .filter(|&i| bits[i / 64] >> (i % 64) & 1 == 1)
+                .collect();
+        }
+        let moves = self.legal_moves_where(|_| true);
+        let mut bits = [0; LEGAL_WORDS];
+        for &i in &moves {
+            bits[i / 64] |= 1 << (i % 64);
+        }
+        self.legal.set(bits);
+        moves
+    }
+
+    // Method to list the indices of the legal moves with the given verb
+    pub fn legal_moves_of_kind(&self, kind: MoveKind) -> Vec<usize> {
+        match self.legal.get() {
+            Some(_) => {
+                let moves = self.legal_moves().into_iter();
+                moves.filter(|&i| MOVES[i].kind() == kind).collect()
+            }
+            None => self.legal_moves_where(|m| m.kind() == kind),
+        }
+    }
+
+    // Method to check whether passing is the only legal move, as after a sacrifice whose
+    // actions have nothing to act on. False once the game is over, when nothing is legal.
+    pub fn must_pass(&self) -> bool {
+        !self.has_legal_action()
+            && self.phase_allows(Move::Pass)
+            && self.clone().process_move(Move::Pass)
+    }
+
+    // Method to check whether any move besides passing is legal, stopping at the first
+    // one found rather than listing them all
+    pub fn has_legal_action(&self) -> bool {
+        if self.legal.get().is_some() {
+            return self.legal_moves().iter().any(|&i| MOVES[i] != Move::Pass);
+        }
+        MOVES
+            .iter()
+            .any(|&m| m != Move::Pass && self.phase_allows(m) && self.clone().process_move(m))
+    }
+
+    // Method to list the legal moves that don't hand the opponent the game on the spot.
+    // This is a convenience for bots, not a rules restriction: the dropped moves stay
+    // legal. A move that opens a sacrifice or movement is only judged by where it leaves
+    // the game, not by how the turn could end.
+    pub fn legal_moves_excluding_suicidal(&self) -> Vec<usize> {
+        let opponent = self.turn.player.inv();
+        self.legal_moves()
+            .into_iter()
+            .filter(|&i| {
+                let mut game = self.clone();
+                game.process_move_idx(i);
+                game.game_result() != GameResult::Win(opponent)
+            })
+            .collect()
+    }
+
+    // Method to list the verbs with at least one legal move, each with those moves,
+    // for menus that pick an action first and its target second
+    pub fn action_menu(&self) -> Vec<(MoveKind, Vec<Move>)> {
+        MoveKind::list()
+            .into_iter()
+            .map(|kind| {
+                let moves = self.legal_moves_of_kind(kind);
+                (kind, moves.into_iter().map(|i| MOVES[i]).collect::<Vec<_>>())
+            })
+            .filter(|(_, moves)| !moves.is_empty())
+            .collect()
+    }
+
+    // Method to test every move accepted by `filter` against a scratch copy of the game
+    // Verbs the current phase rules out (selections after setup, say) are skipped unplayed.
+    fn legal_moves_where(&self, filter: impl Fn(Move) -> bool) -> Vec<usize> {
+        MOVES
+            .iter()
+            .enumerate()
+            .filter(|&(_, &m)| filter(m) && self.phase_allows(m) && self.clone().process_move(m))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    // Method to describe the piece at a board slot, or None past the last slot
+    pub fn piece_at(&self, key: u8) -> Option<PieceInfo> {
+        if key as usize >= PIECE_COUNT {
+            return None;
+        }
+        let key = Key(key);
+        let (size, color) = (key.size(), key.color());
+        Some(match self.board[key] {
+            Piece::Bank => PieceInfo::Bank,
+            Piece::Star { .. } | Piece::BinaryFirst { .. } | Piece::BinarySecond { .. } => {
+                PieceInfo::Star { size, color }
+            }
+            Piece::Ship(ship) => PieceInfo::Ship {
+                size,
+                color,
+                owner: ship.player,
+                system: SystemId(ship.parent),
+            },
+        })
+    }
+
+    // Method to list the abilities `player` can use somewhere on the board, in color
+    // order. A system grants a color's ability to a player with a ship there if its
+    // first star or one of the player's ships there has that color, as the move rules
+    // judge it.
+    pub fn available_abilities(&self, player: Player) -> Vec<Ability> {
+        let mut access = [false; COLOR_COUNT];
+        for key in KeyRange::all() {
+            if let Piece::Ship(ship) = self.board[key] {
+                if ship.player == player {
+                    access[key.color() as usize] = true;
+                    access[ship.parent.color() as usize] = true;
+                }
+            }
+        }
+        Color::list()
+            .into_iter()
+            .filter(|&color| access[color as usize])
+            .map(Ability::for_color)
+            .collect()
+    }
+
+    // Method to flag, by color, the colors `player` could still bring into play. A rough
+    // bound: with construct or transform access anywhere every color is open, and
+    // otherwise only the colors of the player's own ships are. Every color is open
+    // during setup.
+    pub fn reachable_colors(&self, player: Player) -> [bool; COLOR_COUNT] {
+        if self.game_phase() == GamePhase::Setup {
+            return [true; COLOR_COUNT];
+        }
+        let abilities = self.available_abilities(player);
+        if abilities.contains(&Ability::Construct) || abilities.contains(&Ability::Transform) {
+            return [true; COLOR_COUNT];
+        }
+        let mut reachable = [false; COLOR_COUNT];
+        for key in KeyRange::all() {
+            if let Piece::Ship(ship) = self.board[key] {
+                if ship.player == player {
+                    reachable[key.color() as usize] = true;
+                }
+            }
+        }
+        reachable
+    }
+
+    // Method to check whether `player` can attack, move, build and transform somewhere
+    pub fn has_full_spectrum(&self, player: Player) -> bool {
+        self.available_abilities(player).len() == COLOR_COUNT
+    }
+
+    // Method to count the copies of a piece left in the bank. The board is the only
+    // record: a slot holding no star or ship is in the bank.
+    pub fn bank_count(&self, size: Size, color: Color) -> usize {
+        KeyRange::with_color_and_size(color, size)
+            .filter(|&key| self.board[key] == Piece::Bank)
+            .count()
+    }
+
+    // Method to preview how a move would change the bank, indexed by color then size:
+    // positive for pieces it returns (sacrifices, catastrophes), negative for pieces it
+    // takes (selections, constructions), and both for a transform's swap. Collapses at
+    // the end of the turn are included. None if the move is refused.
+    pub fn bank_delta(&self, m: Move) -> Option<[[i8; SIZE_COUNT]; COLOR_COUNT]> {
+        let after = self.with_move(m)?;
+        let mut delta = [[0; SIZE_COUNT]; COLOR_COUNT];
+        for color in Color::list() {
+            for size in Size::list() {
+                let (before, now) = (self.bank_count(size, color), after.bank_count(size, color));
+                delta[color as usize][size as usize] = now as i8 - before as i8;
+            }
+        }
+        Some(delta)
+    }
+
+    // Method to get the ability granted by the sacrifice under way and how many of
+    // its actions remain, or None outside a sacrifice
+    pub fn pending_actions(&self) -> Option<(Ability, u8)> {
+        match self.turn.special {
+            Special::Sacrifice(n, ability) => Some((ability, n)),
+            _ => None,
+        }
+    }
+
+    // Method to get `player`'s home system, None before its first star is chosen or
+    // once its stars have collapsed
+    pub fn homeworld(&self, player: Player) -> Option<SystemId> {
+        self.star_for_ref(player).get().map(SystemId)
+    }
+
+    // Method to gather what brings `player`'s homeworld closer to falling
+    pub fn homeworld_health(&self, player: Player) -> HomeworldHealth {
+        let home = match self.homeworld(player) {
+            Some(home) => home.0,
+            None => return HomeworldHealth::default(),
+        };
+        let mut stars = vec![home];
+        if let Piece::BinaryFirst { sibling, .. } = self.board[home] {
+            stars.extend(sibling.get());
+        }
+        let mut health = HomeworldHealth {
+            stars: stars.len() as u8,
+            ..HomeworldHealth::default()
+        };
+        let mut colors = [0; COLOR_COUNT];
+        for &star in &stars {
+            colors[star.color() as usize] += 1;
+        }
+        for key in KeyRange::all() {
+            let ship = match self.board[key] {
+                Piece::Ship(ship) if ship.parent == home => ship,
+                _ => continue,
+            };
+            colors[key.color() as usize] += 1;
+            if ship.player == player {
+                health.ships += 1;
+            } else if stars.iter().any(|star| star.color() == key.color()) {
+                health.threats += 1;
+            }
+        }
+        health.overpopulated = colors.iter().any(|&n| n >= 4);
+        health
+    }
+
+    // Method to describe what each player has placed of their homeworld, for showing
+    // homeworlds under construction. Once play starts the ship is the player's first
+    // ship at home in ring order (see `sibling_iter`), which is the setup ship for as
+    // long as it stays there.
+    pub fn setup_progress(&self) -> SetupProgress {
+        let piece = |key: Key| (key.size(), key.color());
+        let homeworld = |player: Player| {
+            let Some(home) = self.star_for_ref(player).get() else {
+                return HomeworldSetup::default();
+            };
+            let (child, star2) = match self.board[home] {
+                Piece::Star { child } => (Some(child), None),
+                Piece::BinaryFirst { child, sibling } => (child.get(), sibling.get()),
+                _ => unreachable!(), // Homeworlds are named by their first star
+            };
+            let ship = child.and_then(|child| {
+                let mut ring = self.board.sibling_iter(child);
+                ring.find(|(ship, _)| ship.player == player).map(|(_, key)| key)
+            });
+            HomeworldSetup {
+                star1: Some(piece(home)),
+                star2: star2.map(piece),
+                ship: ship.map(piece),
+            }
+        };
+        SetupProgress {
+            white: homeworld(Player::White),
+            black: homeworld(Player::Black),
+        }
+    }
+
+    // Method to classify the position by how much of the stash is in play and how
+    // many ships each side keeps. A side with two ships or fewer facing four or more
+    // is near elimination, which counts as an endgame however full the bank is.
+    pub fn game_phase(&self) -> GamePhase {
+        if !self.setup_options().is_empty() {
+            return GamePhase::Setup;
+        }
+        let ships = |player: Player| {
+            self.board
+                .pieces
+                .iter()
+                .filter(|piece| matches!(piece, Piece::Ship(ship) if ship.player == player))
+                .count()
+        };
+        let (white, black) = (ships(Player::White), ships(Player::Black));
+        let bank = self.board.pieces.iter().filter(|&&p| p == Piece::Bank).count();
+        if bank <= ENDGAME_BANK || (white.min(black) <= 2 && white.max(black) >= 4) {
+            GamePhase::Endgame
+        } else if PIECE_COUNT - bank <= OPENING_PIECES {
+            GamePhase::Opening
+        } else {
+            GamePhase::Midgame
+        }
+    }
+
+    // Method to count the actions left from the sacrifice under way, 0 outside one.
+    // Spending the last hands the turn over, so this never reads 0 mid-sacrifice.
+    pub fn sacrifice_remaining(&self) -> u8 {
+        self.pending_actions().map_or(0, |(_, n)| n)
+    }
+
+    // Method to sum the sizes of `player`'s ships (small 1, medium 2, large 3) less
+    // the opponent's
+    pub fn material_balance(&self, player: Player) -> i32 {
+        self.board
+            .pieces
+            .iter()
+            .enumerate()
+            .map(|(i, piece)| match piece {
+                Piece::Ship(ship) => {
+                    let size = Key(i as u8).size() as i32 + 1;
+                    if ship.player == player {
+                        size
+                    } else {
+                        -size
+                    }
+                }
+                _ => 0,
+            })
+            .sum()
+    }
+
+    // Method to score `player`'s hold on each color's ability, indexed by color. Per
+    // color the score is the player's ships of that color, plus the systems where a
+    // player's ship sits under a first star of that color, minus the opponent's ships
+    // of that color. Negative means the opponent is the stronger side in that ability.
+    pub fn color_economy(&self, player: Player) -> [i8; COLOR_COUNT] {
+        let mut economy = [0; COLOR_COUNT];
+        let mut stars = Vec::new();
+        for key in KeyRange::all() {
+            if let Piece::Ship(ship) = self.board[key] {
+                if ship.player != player {
+                    economy[key.color() as usize] -= 1;
+                    continue;
+                }
+                economy[key.color() as usize] += 1;
+                if !stars.contains(&ship.parent) {
+                    stars.push(ship.parent);
+                    economy[ship.parent.color() as usize] += 1;
+                }
+            }
+        }
+        economy
+    }
+
+    // Method to find the legal move that most improves the mover's `material_balance`
+    // in one ply, with the improvement. Captures, catastrophes and anything that
+    // collapses as the turn ends all count; ties go to the earliest move in the move
+    // table. A greedy baseline, not a search: (None, 0) if there's no legal move.
+    pub fn best_material_gain(&self) -> (Option<usize>, i32) {
+        let player = self.turn.player;
+        let before = self.material_balance(player);
+        let mut best = (None, 0);
+        for i in self.legal_moves() {
+            let mut child = self.clone();
+            child.process_move_idx(i);
+            let gain = child.material_balance(player) - before;
+            if best.0.is_none() || gain > best.1 {
+                best = (Some(i), gain);
+            }
+        }
+        best
+    }
+
+    // Method to rate how hard the position is to read, for sorting generated puzzles.
+    // The score is the number of legal moves, plus 4 per system on the board, plus 8 per
+    // legal capture or catastrophe, so tactics weigh most and a quiet, sparse board least.
+    pub fn complexity(&self) -> u32 {
+        let moves = self.legal_moves();
+        let tactics = moves
+            .iter()
+            .filter(|&&i| matches!(MOVES[i], Move::Attack(_) | Move::Catastrophe(_)))
+            .count();
+        (moves.len() + 4 * self.systems().len() + 8 * tactics) as u32
+    }
+
+    // Method to get the number of moves accepted so far
+    pub fn ply(&self) -> u32 {
+        self.ply
+    }
+
+    // Method to get the 1-based number of the next move, for transcripts
+    pub fn move_number(&self) -> u32 {
+        self.ply + 1
+    }
+
+    // Method to get the player whose turn it is
+    pub fn to_move(&self) -> Player {
+        self.turn.player
+    }
+
+    // Method to hash the position. Ships are hashed by system and owner rather than
+    // by sibling links, so the same position reached in a different order hashes the same.
+    pub fn hash(&self) -> u64 {
+        let special = self.special_code();
+        let mut h = wyhash64(
+            (self.turn.player as u64) | special << 8,
+            (self.moving_piece.0 as u8 as u64)
+                | (self.wstar.0 as u8 as u64) << 8
+                | (self.bstar.0 as u8 as u64) << 16,
+        );
+        for piece in self.board.pieces {
+            let v = match piece {
+                Piece::Bank => 0,
+                Piece::Star { .. } => 1,
+                Piece::BinaryFirst { .. } => 2,
+                Piece::BinarySecond { sibling } => 3 | (sibling.0 as u64) << 8,
+                Piece::Ship(ship) => {
+                    4 | (ship.parent.0 as u64) << 8 | (ship.player as u64) << 16
+                }
+            };
+            h = wyhash64(h, v);
+        }
+        h
+    }
+
+    // Method to hash the position up to its symmetries, for transposition tables and
+    // tablebases. Three symmetries are applied:
+    // - The three identical pieces of each size and color are interchangeable, so
+    //   systems and ships are hashed by size and color rather than by key.
+    // - The players are interchangeable once roles are normalized, so owners and
+    //   homeworlds are hashed relative to the side to move.
+    // - The colors are interchangeable when relabeled consistently across the whole
+    //   board, abilities granted by sacrifices included, so the hash is the lowest over
+    //   all 24 orderings of the colors. This treats the four abilities as equivalent
+    //   for the game tree's structure, which a strict reading of the rules doesn't.
+    pub fn canonical_hash(&self) -> u64 {
+        let mut lowest = u64::MAX;
+        for a in 0..COLOR_COUNT as u64 {
+            for b in (0..COLOR_COUNT as u64).filter(|&b| b != a) {
+                for c in (0..COLOR_COUNT as u64).filter(|&c| c != a && c != b) {
+                    let d = 6 - a - b - c;
+                    lowest = lowest.min(self.hash_with_colors([a, b, c, d]));
+                }
+            }
+        }
+        lowest
+    }
+
+    // Method to hash the position up to identical pieces and the players' roles only,
+    // so two positions match only if their colors do too
+    fn hash_up_to_copies(&self) -> u64 {
+        self.hash_with_colors([0, 1, 2, 3])
+    }
+
+    // Method to hash the position as in `canonical_hash`, with each color relabeled to
+    // its entry in `colors`
+    fn hash_with_colors(&self, colors: [u64; COLOR_COUNT]) -> u64 {
+        let piece = |key: Key| key.size() as u64 * 4 + colors[key.color() as usize] + 1;
+        let relative = |player: Player| (player != self.turn.player) as u64;
+        let mut systems = ArrayVec::<u64, PIECE_COUNT>::new();
+        for key in KeyRange::all() {
+            let (child, sibling) = match self.board[key] {
+                Piece::Star { child } => (KeyMaybe::some(child), KeyMaybe::none()),
+                Piece::BinaryFirst { child, sibling } => (child, sibling),
+                _ => continue,
+            };
+            // Stars, smaller code first, then whose homeworld this is
+            let mut stars = [piece(key), sibling.get().map_or(0, piece)];
+            stars.sort_unstable();
+            let home = [self.turn.player, self.turn.player.inv()]
+                .iter()
+                .position(|&p| self.star_for_ref(p) == KeyMaybe::some(key))
+                .map_or(0, |i| i as u64 + 1);
+            let mut h = wyhash64(stars[0] | stars[1] << 8, home);
+
+            // Ships by size, color, relative owner and whether they are in motion
+            let mut ships = ArrayVec::<u64, PIECE_COUNT>::new();
+            if let Some(ckey) = child.get() {
+                for (ship, skey) in self.board.sibling_iter(ckey) {
+                    let moving = (self.moving_piece == KeyMaybe::some(skey)) as u64;
+                    ships.push(piece(skey) << 2 | relative(ship.player) << 1 | moving);
+                }
+            }
+            ships.sort_unstable();
+            for v in ships {
+                h = wyhash64(h, v);
+            }
+            systems.push(h);
+        }
+        systems.sort_unstable();
+        // Each ability goes with its color, so a sacrifice's is relabeled the same way
+        let special = match self.turn.special {
+            Special::Sacrifice(n, ability) => 4 | (n as u64) << 4 | colors[ability as usize] << 8,
+            _ => self.special_code(),
+        };
+        let mut h = wyhash64(special, systems.len() as u64);
+        for v in systems {
+            h = wyhash64(h, v);
+        }
+        h
+    }
+
+    // Method to encode the phase of the current turn for hashing
+    fn special_code(&self) -> u64 {
+        match self.turn.special {
+            Special::None => 0,
+            Special::Star1 => 1,
+            Special::Star2 => 2,
+            Special::Ship => 3,
+            Special::Sacrifice(n, ability) => 4 | (n as u64) << 4 | (ability as u64) << 8,
+        }
+    }
+
+    // Method to check whether a player's homeworld is gone or empty of their ships
+    fn homeworld_lost(&self, player: Player) -> bool {
+        let child = match self.star_for_ref(player).get().map(|key| self.board[key]) {
+            Some(Piece::Star { child }) => KeyMaybe::some(child),
+            Some(Piece::BinaryFirst { child, .. }) => child,
+            _ => return true,
+        };
+        match child.get() {
+            Some(ckey) => !self
+                .board
+                .sibling_iter(ckey)
+                .any(|(ship, _)| ship.player == player),
+            None => true,
+        }
+    }
+
+    // Method to determine whether the game has been decided. Homeworlds are only
+    // judged between turns, so a player may empty theirs mid-sacrifice or mid-move.
+    pub fn game_result(&self) -> GameResult {
+        match self.turn.special {
+            Special::None if self.moving_piece.is_none() => {}
+            _ => return GameResult::Ongoing,
+        }
+        match (
+            self.homeworld_lost(Player::White),
+            self.homeworld_lost(Player::Black),
+        ) {
+            (false, false) => GameResult::Ongoing,
+            (true, false) => GameResult::Win(Player::Black),
+            (false, true) => GameResult::Win(Player::White),
+            (true, true) => match (self.ruleset.mutual_destruction, self.collapsed_by) {
+                (MutualDestruction::MoverLoses, Some(mover)) => GameResult::Win(mover.inv()),
+                _ => GameResult::Draw,
+            },
+        }
+    }
+
+    // Method to check whether the board has decided the game
+    pub fn is_terminal(&self) -> bool {
+        self.game_result() != GameResult::Ongoing
+    }
+
+    // Method to list the legal moves that win for the player to move. A move that
+    // opens a multi-action turn counts if the player can go on to win before the turn
+    // ends: a sacrifice if one of its `sacrifice_plans` wins, a movement if it can
+    // arrive somewhere that wins.
+    pub fn winning_moves(&self) -> Vec<usize> {
+        let player = self.turn.player;
+        let sacrifices: Vec<usize> =
+            self.winning_sacrifice_plans().iter().map(|plan| plan[0]).collect();
+        let mut memo = SearchMemo::default();
+        self.legal_moves()
+            .into_iter()
+            .filter(|&i| match MOVES[i] {
+                Move::Sacrifice(_) => sacrifices.contains(&i),
+                _ => {
+                    let mut game = self.clone();
+                    game.process_move_idx(i);
+                    game.wins_within_turn(player, &mut memo)
+                }
+            })
+            .collect()
+    }
+
+    // Method to list every way the player to move can play out a sacrifice: a legal
+    // sacrifice, then the moves it grants, up to where the turn passes or the game ends.
+    // Each plan is a list of move table indices.
+    pub fn sacrifice_plans(&self) -> Vec<Vec<usize>> {
+        self.collect_sacrifice_plans(|_| true)
+    }
+
+    // Method to list the sacrifice plans after which the player to move has won
+    pub fn winning_sacrifice_plans(&self) -> Vec<Vec<usize>> {
+        let player = self.turn.player;
+        self.collect_sacrifice_plans(|game| game.game_result() == GameResult::Win(player))
+    }
+
+    // Method to list the sacrifice plans whose final position passes `keep`
+    fn collect_sacrifice_plans(&self, keep: impl Fn(&Game) -> bool) -> Vec<Vec<usize>> {
+        let mut plans = Vec::new();
+        for i in self.legal_moves_of_kind(MoveKind::Sacrifice) {
+            let mut game = self.clone();
+            game.process_move_idx(i);
+            game.extend_plan(self.turn.player, &mut vec![i], &keep, &mut plans);
+        }
+        plans
+    }
+
+    // Method to play on from partway through `plan` until `player`'s turn is over,
+    // recording each finished plan that passes `keep`
+    fn extend_plan(
+        &self,
+        player: Player,
+        plan: &mut Vec<usize>,
+        keep: &impl Fn(&Game) -> bool,
+        plans: &mut Vec<Vec<usize>>,
+    ) {
+        if self.turn.player != player || self.is_terminal() {
+            if keep(self) {
+                plans.push(plan.clone());
+            }
+            return;
+        }
+        for i in self.legal_moves() {
+            let mut game = self.clone();
+            game.process_move_idx(i);
+            plan.push(i);
+            game.extend_plan(player, plan, keep, plans);
+            plan.pop();
+        }
+    }
+
+    // Method to find the fewest plies (moves from the move table, each action and pass
+    // counting as one) in which the player to move can force a win, trying every line
+    // up to `max_depth` deep. None if there is no forced win that soon.
+    pub fn mate_in(&self, max_depth: u8) -> Option<u8> {
+        let player = self.turn.player;
+        (1..=max_depth).find(|&depth| self.forces_win(player, depth))
+    }
+
+    // Method to check whether `player` wins within `depth` plies whatever the opponent
+    // plays. Turns can span several plies, so the side choosing follows the turn.
+    fn forces_win(&self, player: Player, depth: u8) -> bool {
+        match self.game_result() {
+            GameResult::Win(p) | GameResult::Resignation(p) => return p == player,
+            GameResult::Draw | GameResult::AgreedDraw => return false,
+            GameResult::Ongoing if depth == 0 => return false,
+            GameResult::Ongoing => {}
+        }
+        let moves = self.legal_moves();
+        let wins = |i: usize| {
+            let mut game = self.clone();
+            game.process_move_idx(i);
+            game.forces_win(player, depth - 1)
+        };
+        if self.turn.player == player {
+            moves.into_iter().any(wins)
+        } else {
+            !moves.is_empty() && moves.into_iter().all(wins)
+        }
+    }
+
+    // Method to check whether playing `m` leaves the mover open to losing on the
+    // opponent's next turn, however the mover finishes their own. A move that opens a
+    // sacrifice or movement only counts if every way of completing the turn leaves the
+    // opponent a win, which may take a whole sacrifice to reach. False if `m` is refused.
+    pub fn opponent_best_response_loses_me(&self, m: Move) -> bool {
+        let mover = self.turn.player;
+        match self.with_move(m) {
+            Some(game) => {
+                game.exposed(mover, &mut SearchMemo::default(), &mut SearchMemo::default())
+            }
+            None => false,
+        }
+    }
+
+    // Method to check whether the opponent of `mover` wins by the end of their next turn
+    // whatever `mover` does with the rest of theirs
+    fn exposed(
+        &self,
+        mover: Player,
+        memo: &mut SearchMemo,
+        opponent_memo: &mut SearchMemo,
+    ) -> bool {
+        match self.game_result() {
+            GameResult::Win(p) | GameResult::Resignation(p) => return p != mover,
+            GameResult::Draw | GameResult::AgreedDraw => return false,
+            GameResult::Ongoing => {}
+        }
+        if self.turn.player != mover {
+            return self.wins_within_turn(mover.inv(), opponent_memo);
+        }
+        // Going round in circles saves nothing
+        memo.visit(self.hash(), true, |memo| {
+            self.legal_moves().into_iter().all(|i| {
+                let mut game = self.clone();
+                game.process_move_idx(i);
+                game.exposed(mover, memo, opponent_memo)
+            })
+        })
+    }
+
+    // Method to drop the moves in `moves` that lead to the same position, up to identical
+    // pieces, as an earlier one, along with any that are refused. The canonical-key rule
+    // already keeps most duplicates out of `legal_moves`; this catches the rest.
+    pub fn dedupe_moves(&self, moves: &[usize]) -> Vec<usize> {
+        let mut seen = std::collections::HashSet::new();
+        moves
+            .iter()
+            .copied()
+            .filter(|&i| {
+                let mut game = self.clone();
+                if !game.process_move_idx(i) {
+                    return false;
+                }
+                // The hash is relative to the side to move, so keep that apart
+                seen.insert((game.to_move() == Player::White, game.hash_up_to_copies()))
+            })
+            .collect()
+    }
+
+    // Method to list the player to move's legal moves that stop the opponent playing
+    // `threat` at the start of their next turn. A move that opens a sacrifice or
+    // movement counts if some way of finishing the turn stops it; a move that loses
+    // outright never does.
+    pub fn defensive_moves(&self, threat: Move) -> Vec<usize> {
+        let mover = self.turn.player;
+        let mut memo = SearchMemo::default();
+        self.legal_moves()
+            .into_iter()
+            .filter(|&i| {
+                let mut game = self.clone();
+                game.process_move_idx(i);
+                game.threat_blocked(mover, threat, &mut memo)
+            })
+            .collect()
+    }
+
+    // Method to check whether `mover` can finish their turn with the opponent unable to
+    // play `threat`
+    fn threat_blocked(&self, mover: Player, threat: Move, memo: &mut SearchMemo) -> bool {
+        match self.game_result() {
+            GameResult::Win(p) | GameResult::Resignation(p) => return p == mover,
+            GameResult::Draw | GameResult::AgreedDraw => return true,
+            GameResult::Ongoing => {}
+        }
+        if self.turn.player != mover {
+            return self.with_move(threat).is_none();
+        }
+        // Going round in circles blocks nothing
+        memo.visit(self.hash(), false, |memo| {
+            self.legal_moves().into_iter().any(|i| {
+                let mut game = self.clone();
+                game.process_move_idx(i);
+                game.threat_blocked(mover, threat, memo)
+            })
+        })
+    }
+
+    // Method to check whether `player` has won, or can still win before their turn ends
+    fn wins_within_turn(&self, player: Player, memo: &mut SearchMemo) -> bool {
+        match self.game_result() {
+            GameResult::Win(p) | GameResult::Resignation(p) => return p == player,
+            GameResult::Draw | GameResult::AgreedDraw => return false,
+            GameResult::Ongoing => {}
+        }
+        if self.turn.player != player {
+            return false;
+        }
+        // Going round in circles wins nothing
+        memo.visit(self.hash(), false, |memo| {
+            self.legal_moves().into_iter().any(|i| {
+                let mut game = self.clone();
+                game.process_move_idx(i);
+                game.wins_within_turn(player, memo)
+            })
+        })
+    }
+
+    // Method to list the systems one piece of a color short of overpopulating where
+    // `player` stands to lose something, with how many of their pieces would go: their
+    // ships of that color, and their home stars of it. Already overpopulated systems
+    // are `overpopulations`' business.
+    pub fn catastrophe_vulnerabilities(&self, player: Player) -> Vec<(SystemId, Color, u8)> {
+        let mut vulnerabilities = Vec::new();
+        for system in self.systems() {
+            let star = system.0;
+            let (child, partner) = match self.board[star] {
+                Piece::Star { child } => (Some(child), None),
+                Piece::BinaryFirst { child, sibling } => (child.get(), sibling.get()),
+                _ => unreachable!(), // Systems are named by their first star
+            };
+            let home = self.homeworld(player) == Some(system);
+            let mut pieces = [0; COLOR_COUNT];
+            let mut at_risk = [0; COLOR_COUNT];
+            for key in std::iter::once(star).chain(partner) {
+                pieces[key.color() as usize] += 1;
+                at_risk[key.color() as usize] += home as u8;
+            }
+            let ships = child.into_iter().flat_map(|child| self.board.sibling_iter(child));
+            for (ship, key) in ships {
+                pieces[key.color() as usize] += 1;
+                at_risk[key.color() as usize] += (ship.player == player) as u8;
+            }
+            for color in Color::list() {
+                let c = color as usize;
+                if pieces[c] == 3 && at_risk[c] > 0 {
+                    vulnerabilities.push((system, color, at_risk[c]));
+                }
+            }
+        }
+        vulnerabilities
+    }
+
+    // Method to list the ships naming each standing overpopulation (the lowest ship of
+    // the overpopulated color in its system), whether or not a piece is in motion
+    pub fn overpopulations(&self) -> Vec<Key> {
+        let mut scratch = self.clone();
+        scratch.moving_piece = KeyMaybe::none();
+        KeyRange::all()
+            .filter(|&key| scratch.clone().process_catastrophe(key))
+            .collect()
+    }
+
+    // Method to list the ships the player to move may name in `Move::Catastrophe` right
+    // now. Unlike `overpopulations`, this leaves out everything while a ship is in
+    // flight, and under `CatastropheCost::Action` whenever no action is left to spend.
+    pub fn legal_catastrophes(&self) -> Vec<Key> {
+        KeyRange::all()
+            .filter(|&key| self.clone().process_move(Move::Catastrophe(key)))
+            .collect()
+    }
+
+    // Method to list the legal moves that bring a system to four pieces of one color,
+    // each with that system and color. Only new overpopulations the mover gets to use
+    // count: either the turn is still theirs after the move, or overpopulations collapse
+    // on their own when it ends (`CatastropheMode::Auto`). In manual mode a move that
+    // ends the turn leaves the catastrophe to the opponent, so it isn't listed.
+    pub fn catastrophe_setups(&self) -> Vec<(usize, SystemId, Color)> {
+        let standing = |game: &Game| -> Vec<(SystemId, Color)> {
+            game.overpopulations()
+                .into_iter()
+                .map(|key| match game.board[key] {
+                    Piece::Ship(ship) => (SystemId(ship.parent), key.color()),
+                    _ => unreachable!(), // Catastrophes are always named by a ship
+                })
+                .collect()
+        };
+        let before = standing(self);
+        let mover = self.turn.player;
+        let manual = self.ruleset.catastrophe_mode == CatastropheMode::Manual;
+        let mut setups = Vec::new();
+        for i in self.legal_moves() {
+            let mut scratch = self.clone();
+            // Keep the overpopulation standing to look at
+            scratch.ruleset.catastrophe_mode = CatastropheMode::Manual;
+            scratch.process_move_idx(i);
+            if scratch.turn.player != mover && manual {
+                continue;
+            }
+            for (system, color) in standing(&scratch) {
+                if !before.contains(&(system, color)) {
+                    setups.push((i, system, color));
+                }
+            }
+        }
+        setups
+    }
+
+    // Method to list the keys a catastrophe named by `key` would return to the bank,
+    // stars included, or None if that catastrophe can't be triggered now
+    pub fn catastrophe_preview(&self, key: u8) -> Option<Vec<u8>> {
+        if key as usize >= PIECE_COUNT {
+            return None;
+        }
+        let mut scratch = self.clone();
+        if !scratch.process_catastrophe(Key(key)) {
+            return None;
+        }
+        Some(
+            KeyRange::all()
+                .filter(|&k| self.board[k] != Piece::Bank && scratch.board[k] == Piece::Bank)
+                .map(|k| k.0)
+                .collect(),
+        )
+    }
+
+    // Method to count how many of the actions granted by sacrificing the ship at `key`
+    // its owner could actually spend, as if it were their turn now, taking the first
+    // legal action each time. None if the ship couldn't be sacrificed.
+    pub(crate) fn sacrifice_yield(&self, key: u8) -> Option<u8> {
+        let owner = match self.board.pieces.get(key as usize) {
+            Some(Piece::Ship(ship)) => ship.player,
+            _ => return None,
+        };
+        let mut scratch = self.clone();
+        scratch.turn = Turn {
+            player: owner,
+            special: Special::None,
+        };
+        scratch.moving_piece = KeyMaybe::none();
+        if !scratch.process_sacrifice(Key(key)) {
+            return None;
+        }
+        let mut spent = 0;
+        while let Special::Sacrifice(_, ability) = scratch.turn.special {
+            let Some(&i) = scratch.legal_moves_of_kind(ability.kind()).first() else {
+                break;
+            };
+            scratch.process_move_idx(i);
+            // A movement is only spent once it arrives somewhere
+            if scratch.moving_piece.is_some() {
+                let Some(&i) = scratch.legal_moves_of_kind(MoveKind::Move).first() else {
+                    break;
+                };
+                scratch.process_move_idx(i);
+            }
+            spent += 1;
+        }
+        Some(spent)
+    }
+
+    // Method to pick the ship whose sacrifice, right now, grants the most actions that
+    // could actually be spent, with that count. Equal counts go to the smaller ship, as
+    // it costs less material, then to the lower key. None if no sacrifice is legal.
+    pub fn best_sacrifice(&self) -> Option<(Key, u8)> {
+        self.legal_moves_of_kind(MoveKind::Sacrifice)
+            .into_iter()
+            .filter_map(|i| match MOVES[i] {
+                Move::Sacrifice(key) => Some((key, self.sacrifice_yield(key.0)?)),
+                _ => None,
+            })
+            .min_by_key(|&(key, spent)| (std::cmp::Reverse(spent), key.size(), key))
+    }
+
+    // Method to check whether `player`, starting a turn now, could do nothing but pass.
+    // Sacrifices that grant no usable action don't count as a way out. This is advice for
+    // a UI and doesn't affect the result; it's false during setup and once the game is over.
+    pub fn is_stuck(&self, player: Player) -> bool {
+        if self.is_terminal() || !self.setup_options().is_empty() {
+            return false;
+        }
+        let mut scratch = self.clone();
+        scratch.turn = Turn {
+            player,
+            special: Special::None,
+        };
+        scratch.moving_piece = KeyMaybe::none();
+        scratch.legal_moves().into_iter().all(|i| match MOVES[i] {
+            Move::Pass => true,
+            Move::Sacrifice(key) => scratch.sacrifice_yield(key.0) == Some(0),
+            _ => false,
+        })
+    }
+
+    // Method to list the systems currently on the board, in key order
+    pub fn systems(&self) -> Vec<SystemId> {
+        KeyRange::all()
+            .filter(|&key| {
+                matches!(
+                    self.board[key],
+                    Piece::Star { .. } | Piece::BinaryFirst { .. }
+                )
+            })
+            .map(SystemId)
+            .collect()
+    }
+
+    // Method to list each current system with its star sizes, first star first. A
+    // single star's size appears twice, so two systems are connected exactly when
+    // their pairs share no size.
+    pub fn star_size_map(&self) -> Vec<(SystemId, (Size, Size))> {
+        self.systems()
+            .into_iter()
+            .map(|system| (system, self.get_star_sizes(system.0)))
+            .collect()
+    }
+
+    // Method to get a system's stars, first star first, and its ships in ring order, in
+    // the form `new_from_board` takes. None if `system` isn't on the board.
+    pub fn system_contents(&self, system: SystemId) -> Option<SystemSpec> {
+        let (child, second) = match self.board[system.0] {
+            Piece::Star { child } => (KeyMaybe::some(child), KeyMaybe::none()),
+            Piece::BinaryFirst { child, sibling } => (child, sibling),
+            _ => return None,
+        };
+        let stars = std::iter::once(system.0)
+            .chain(second.get())
+            .map(|key| (key.size(), key.color()))
+            .collect();
+        let ships = child.get().map_or(Vec::new(), |ckey| {
+            self.board
+                .sibling_iter(ckey)
+                .map(|(ship, skey)| (skey.size(), skey.color(), ship.player))
+                .collect()
+        });
+        Some(SystemSpec { stars, ships })
+    }
+
+    // Method to list the current systems a ship in `system` could travel to, in key
+    // order. Systems a ship could discover aren't included. Empty if `system` isn't on
+    // the board.
+    pub fn move_destinations(&self, system: SystemId) -> Vec<SystemId> {
+        let systems = self.star_size_map();
+        let Some(&(_, sizes)) = systems.iter().find(|&&(other, _)| other == system) else {
+            return Vec::new();
+        };
+        systems
+            .into_iter()
+            .filter(|&(_, other)| sizes_connected(sizes, other))
+            .map(|(other, _)| other)
+            .collect()
+    }
+
+    // Method to count `player`'s ships in each system holding at least one, in key order
+    pub fn fleet_distribution(&self, player: Player) -> Vec<(SystemId, u8)> {
+        self.systems()
+            .into_iter()
+            .filter_map(|system| {
+                let child = match self.board[system.0] {
+                    Piece::Star { child } => Some(child),
+                    Piece::BinaryFirst { child, .. } => child.get(),
+                    _ => unreachable!(), // Systems are named by their first star
+                }?;
+                let count = self
+                    .board
+                    .sibling_iter(child)
+                    .filter(|(ship, _)| ship.player == player)
+                    .count();
+                (count > 0).then_some((system, count as u8))
+            })
+            .collect()
+    }
+
+    // Method to list every pair of current systems a ship could travel between, each
+    // pair once with the lower system first
+    pub fn reachability(&self) -> Vec<(SystemId, SystemId)> {
+        let systems = self.star_size_map();
+        let mut pairs = Vec::new();
+        for (i, &(a, asizes)) in systems.iter().enumerate() {
+            for &(b, bsizes) in &systems[i + 1..] {
+                if sizes_connected(asizes, bsizes) {
+                    pairs.push((a, b));
+                }
+            }
+        }
+        pairs
+    }
+
+    // Method to draw the whole position as text: whose turn it is, each system with its
+    // stars and ships, and what remains in the bank
+    pub fn render(&self) -> String {
+        self.render_for(Player::White, Fog::Open)
+    }
+
+    // Method to draw the position as `viewer` sees it with `fog` hiding what the variant
+    // keeps secret. With no fog this matches `render`.
+    pub fn render_for(&self, viewer: Player, fog: Fog) -> String {
+        let name = |key: Key| format!("{} {}", key.size().to_str(), key.color().to_str());
+        let mut out = format!("{:?} to move", self.turn.player);
+        match (self.turn.special, self.moving_piece.get()) {
+            (_, Some(key)) => out += &format!(", moving {}", name(key)),
+            (Special::Sacrifice(n, ability), None) => {
+                out += &format!(", {} {:?} action(s) left", n, ability)
+            }
+            _ => {}
+        }
+        out.push('\n');
+
+        for system in self.systems() {
+            let star = system.0;
+            let label = match [Player::White, Player::Black]
+                .into_iter()
+                .find(|&p| self.star_for_ref(p) == KeyMaybe::some(star))
+            {
+                Some(p) => format!("{:?} home", p),
+                None => format!("System {}", star.0),
+            };
+            let mut stars = name(star);
+            if let Piece::BinaryFirst { sibling, .. } = self.board[star] {
+                if let Some(skey) = sibling.get() {
+                    stars += &format!(" + {}", name(skey));
+                }
+            }
+            let ships: Vec<String> = KeyRange::all()
+                .filter_map(|key| match self.board[key] {
+                    Piece::Ship(ship) if ship.parent == star => Some((ship.player, key)),
+                    _ => None,
+                })
+                .map(|(owner, key)| match fog {
+                    Fog::HideOpponentSizes if owner != viewer => {
+                        format!("{:?} ? {}", owner, key.color().to_str())
+                    }
+                    _ => format!("{:?} {}", owner, name(key)),
+                })
+                .collect();
+            out += &format!("{}: {} | {}\n", label, stars, ships.join(", "));
+        }
+
+        if fog == Fog::HideBank {
+            out += "Bank: hidden\n";
+        } else {
+            let mut bank = Vec::new();
+            for color in Color::list() {
+                for size in Size::list() {
+                    let n = self.bank_count(size, color);
+                    if n > 0 {
+                        bank.push(format!("{} {} x{}", size.to_str(), color.to_str(), n));
+                    }
+                }
+            }
+            out += &format!("Bank: {}\n", bank.join(", "));
+        }
+        out
+    }
+
+    /// Constructor method to set up a position from a system list, one system per line
+    /// as `name: stars | white ships | black ships`, plus a `turn:` line. This is the
+    /// crate's own plain-text format; it doesn't read homeworlds.io exports or those of
+    /// any other Homeworlds site. Pieces are a color letter and a size, `g3` for a large
+    /// green. The systems named `white` and `black` are the homeworlds and may hold two
+    /// stars; any other name is a colony with one star and at least one ship. The first
+    /// star listed leads a binary. An optional `rules:` line of `key=value` words, as
+    /// `to_system_list` writes for variants, sets the `RuleSet`; rules it leaves out keep
+    /// their defaults.
+    ///
+    /// ```
+    /// use starlight_engine::game::{Game, GameResult};
+    ///
+    /// let game = Game::from_system_list(
+    ///     "white: g3 b1 | y3 |
+    ///      black: y2 g2 | | y1
+    ///      scout: r2 | g1 | b2
+    ///      turn: black",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(game.game_result(), GameResult::Ongoing);
+    /// let text = game.to_system_list();
+    /// assert_eq!(Game::from_system_list(&text).unwrap().to_system_list(), text);
+    /// ```
+    pub fn from_system_list(s: &str) -> Result<Game, SystemListError> {
+        let mut game = Game::new();
+        let mut turn = None;
+        for (i, text) in s.lines().map(str::trim).enumerate() {
+            let line = i + 1;
+            if text.is_empty() {
+                continue;
+            }
+            let (name, rest) = text.split_once(':').ok_or(SystemListError::Syntax { line })?;
+            let name = name.trim();
+            if name == "turn" {
+                turn = Some(match rest.trim() {
+                    "white" => Player::White,
+                    "black" => Player::Black,
+                    _ => return Err(SystemListError::Syntax { line }),
+                });
+                continue;
+            }
+            if name == "rules" {
+                if !rest.split_whitespace().all(|word| game.ruleset.apply_system_list(word)) {
+                    return Err(SystemListError::Syntax { line });
+                }
+                continue;
+            }
+            let fields: Vec<&str> = rest.split('|').collect();
+            let [stars, white, black] = fields[..] else {
+                return Err(SystemListError::Syntax { line });
+            };
+            let home = match name {
+                "white" => Some(Player::White),
+                "black" => Some(Player::Black),
+                _ => None,
+            };
+
+            let piece = |token: &str| {
+                let mut chars = token.chars();
+                let color = match chars.next() {
+                    Some('r') => Color::Red,
+                    Some('y') => Color::Yellow,
+                    Some('g') => Color::Green,
+                    Some('b') => Color::Blue,
+                    _ => return Err(SystemListError::BadPiece { line }),
+                };
+                let size = match chars.as_str() {
+                    "1" => Size::Small,
+                    "2" => Size::Medium,
+                    "3" => Size::Large,
+                    _ => return Err(SystemListError::BadPiece { line }),
+                };
+                Ok((size, color))
+            };
+            let mut spec = SystemSpec {
+                stars: stars.split_whitespace().map(piece).collect::<Result<_, _>>()?,
+                ships: Vec::new(),
+            };
+            for (player, field) in [(Player::White, white), (Player::Black, black)] {
+                for token in field.split_whitespace() {
+                    let (size, color) = piece(token)?;
+                    spec.ships.push((size, color, player));
+                }
+            }
+            let star = game.add_system(&spec, home.is_some()).map_err(|fault| match fault {
+                SystemFault::BadSystem => SystemListError::BadSystem { line },
+                SystemFault::OutOfPieces => SystemListError::OutOfPieces { line },
+            })?;
+            if let Some(player) = home {
+                if game.star_for_ref(player).is_some() {
+                    return Err(SystemListError::Homeworld(player));
+                }
+                *game.star_for(player) = KeyMaybe::some(star);
+            }
+        }
+        for player in [Player::White, Player::Black] {
+            if game.star_for_ref(player).is_none() {
+                return Err(SystemListError::Homeworld(player));
+            }
+        }
+        game.turn = Turn {
+            player: turn.ok_or(SystemListError::NoTurn)?,
+            special: Special::None,
+        };
+        Ok(game)
+    }
+
+    // Constructor method to set up a position directly: every system with its stars
+    // and ships, which two of them are the homeworlds, and who moves next. Pieces come
+    // out of the bank lowest copy first, as the moves would take them, so a homeworld is
+    // named by the `SystemId` that `SystemSpec::ids` gives its system. The game starts
+    // outside setup, at the beginning of `to_move`'s turn.
+    pub fn new_from_board(
+        systems: &[SystemSpec],
+        white_home: SystemId,
+        black_home: SystemId,
+        to_move: Player,
+    ) -> Result<Game, SetupError> {
+        let ids = SystemSpec::ids(systems);
+        let index = |player: Player, home: SystemId| {
+            match ids.iter().position(|&id| id == Some(home)) {
+                Some(i) if white_home != black_home => Ok(i),
+                _ => Err(SetupError::Homeworld(player)),
+            }
+        };
+        let homes = [
+            (Player::White, index(Player::White, white_home)?),
+            (Player::Black, index(Player::Black, black_home)?),
+        ];
+        let mut game = Game::new();
+        for (i, spec) in systems.iter().enumerate() {
+            let home = homes.iter().find(|&&(_, h)| h == i).map(|&(p, _)| p);
+            let star = game.add_system(spec, home.is_some()).map_err(|fault| match fault {
+                SystemFault::BadSystem => SetupError::BadSystem { system: i },
+                SystemFault::OutOfPieces => SetupError::OutOfPieces { system: i },
+            })?;
+            if let Some(player) = home {
+                *game.star_for(player) = KeyMaybe::some(star);
+            }
+        }
+        game.turn = Turn {
+            player: to_move,
+            special: Special::None,
+        };
+        game.check_invariants().map_err(SetupError::Invariant)?;
+        Ok(game)
+    }
+
+    // Method to take a system's pieces from the bank, the lowest free copy of each, and
+    // link them: the stars as a pair under the first, the ships into a ring beneath it.
+    // Returns the first star. Only homeworlds may have two stars or no ships.
+    fn add_system(&mut self, spec: &SystemSpec, home: bool) -> Result<Key, SystemFault> {
+        let max_stars = if home { 2 } else { 1 };
+        let colony_empty = !home && spec.ships.is_empty();
+        if spec.stars.is_empty() || spec.stars.len() > max_stars || colony_empty {
+            return Err(SystemFault::BadSystem);
+        }
+        let mut take = |size: Size, color: Color| {
+            let key = KeyRange::with_color_and_size(color, size)
+                .find(|&key| self.board[key] == Piece::Bank)
+                .ok_or(SystemFault::OutOfPieces)?;
+            self.board[key] = Piece::BinarySecond { sibling: key }; // Held until linked
+            Ok(key)
+        };
+        let stars = spec
+            .stars
+            .iter()
+            .map(|&(size, color)| take(size, color))
+            .collect::<Result<Vec<Key>, _>>()?;
+        let ships = spec
+            .ships
+            .iter()
+            .map(|&(size, color, player)| Ok((take(size, color)?, player)))
+            .collect::<Result<Vec<(Key, Player)>, _>>()?;
+
+        let star = stars[0];
+        let child = ships.first().map(|&(key, _)| key);
+        self.board[star] = match home {
+            true => Piece::BinaryFirst {
+                child: child.map_or(KeyMaybe::none(), KeyMaybe::some),
+                sibling: stars.get(1).map_or(KeyMaybe::none(), |&v| KeyMaybe::some(v)),
+            },
+            false => Piece::Star {
+                child: child.unwrap(),
+            },
+        };
+        if let Some(&second) = stars.get(1) {
+            self.board[second] = Piece::BinarySecond { sibling: star };
+        }
+        for (j, &(key, player)) in ships.iter().enumerate() {
+            self.board[key] = Piece::Ship(Ship {
+                parent: star,
+                sibling: ships[(j + 1) % ships.len()].0,
+                player,
+            });
+        }
+        Ok(star)
+    }
+
+    // Method to write the position in the system list format `from_system_list` reads. Only
+    // whose turn it is survives, not any sacrifice or movement under way. Variant rules
+    // get a `rules:` line; standard games don't need one.
+    pub fn to_system_list(&self) -> String {
+        let piece = |key: Key| {
+            let color = key.color().to_str().chars().next().unwrap();
+            format!("{}{}", color, key.size() as u8 + 1)
+        };
+        let mut out = String::new();
+        for system in self.systems() {
+            let star = system.0;
+            let name = match [Player::White, Player::Black]
+                .into_iter()
+                .find(|&p| self.star_for_ref(p) == KeyMaybe::some(star))
+            {
+                Some(Player::White) => "white".to_string(),
+                Some(Player::Black) => "black".to_string(),
+                None => format!("s{}", star.0),
+            };
+            let mut stars = vec![piece(star)];
+            if let Piece::BinaryFirst { sibling, .. } = self.board[star] {
+                stars.extend(sibling.get().map(piece));
+            }
+            let ships = |player: Player| {
+                KeyRange::all()
+                    .filter(|&key| match self.board[key] {
+                        Piece::Ship(ship) => ship.parent == star && ship.player == player,
+                        _ => false,
+                    })
+                    .map(piece)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+            let line = format!(
+                "{}: {} | {} | {}",
+                name,
+                stars.join(" "),
+                ships(Player::White),
+                ships(Player::Black)
+            );
+            out += line.trim_end();
+            out.push('\n');
+        }
+        let turn = match self.turn.player {
+            Player::White => "white",
+            Player::Black => "black",
+        };
+        out += &format!("turn: {}\n", turn);
+        if self.ruleset != RuleSet::default() {
+            out += &format!("rules: {}\n", self.ruleset.to_system_list());
+        }
+        out
+    }
+
+    // Method to describe the position on one line for test assertions, e.g.
+    // `W:(g3,b1)[y3] B:(y2,g1)[g3*] | S1:(b2)[g1,r1*] ; w`: each homeworld (`-` if
+    // gone), then the colonies numbered in `systems` order, then the player to move.
+    // Stars come in parentheses and ships in brackets by key, Black's marked with `*`.
+    pub fn to_compact(&self) -> String {
+        let piece = |key: Key| {
+            let color = key.color().to_str().chars().next().unwrap();
+            format!("{}{}", color, key.size() as u8 + 1)
+        };
+        let system = |star: Key| {
+            let mut stars = vec![piece(star)];
+            if let Piece::BinaryFirst { sibling, .. } = self.board[star] {
+                stars.extend(sibling.get().map(piece));
+            }
+            let ships: Vec<String> = KeyRange::all()
+                .filter_map(|key| match self.board[key] {
+                    Piece::Ship(ship) if ship.parent == star => Some(match ship.player {
+                        Player::White => piece(key),
+                        Player::Black => piece(key) + "*",
+                    }),
+                    _ => None,
+                })
+                .collect();
+            format!("({})[{}]", stars.join(","), ships.join(","))
+        };
+        let home = |player: Player| match self.homeworld(player) {
+            Some(home) => system(home.0),
+            None => "-".to_string(),
+        };
+        let colonies: Vec<String> = self
+            .systems()
+            .into_iter()
+            .filter(|&id| {
+                let homes = [self.homeworld(Player::White), self.homeworld(Player::Black)];
+                !homes.contains(&Some(id))
+            })
+            .enumerate()
+            .map(|(i, id)| format!(" S{}:{}", i + 1, system(id.0)))
+            .collect();
+        let turn = match self.turn.player {
+            Player::White => 'w',
+            Player::Black => 'b',
+        };
+        format!(
+            "W:{} B:{} |{} ; {}",
+            home(Player::White),
+            home(Player::Black),
+            colonies.concat(),
+            turn
+        )
+    }
+
+    // Method to relabel every piece's color, `perm[c as usize]` taking the place of `c`,
+    // for augmenting training data. Abilities stay tied to colors, so they move with the
+    // relabeling: a red star that granted attacks becomes, say, a blue star granting
+    // transforms. The result is only equivalent in play when `perm` maps the colors that
+    // matter onto ones with the same role (swapping two colors neither player can use,
+    // say). A sacrifice under way is relabeled too, so its remaining actions use the
+    // ability of its new color. Panics unless `perm` names every color once.
+    pub fn permute_colors(&self, perm: [Color; COLOR_COUNT]) -> Game {
+        assert!(
+            Color::list().iter().all(|c| perm.contains(c)),
+            "not a permutation of the colors"
+        );
+        let per_color = (SIZE_COUNT * COPY_COUNT) as u8;
+        let map = |key: Key| {
+            Key(perm[key.color() as usize] as u8 * per_color + key.0 % per_color)
+        };
+        let map_maybe = |key: KeyMaybe| {
+            key.get()
+                .map_or(KeyMaybe::none(), |k| KeyMaybe::some(map(k)))
+        };
+        let mut game = self.clone();
+        for key in KeyRange::all() {
+            game.board[map(key)] = match self.board[key] {
+                Piece::Bank => Piece::Bank,
+                Piece::Star { child } => Piece::Star { child: map(child) },
+                Piece::BinaryFirst { child, sibling } => Piece::BinaryFirst {
+                    child: map_maybe(child),
+                    sibling: map_maybe(sibling),
+                },
+                Piece::BinarySecond { sibling } => Piece::BinarySecond {
+                    sibling: map(sibling),
+                },
+                Piece::Ship(ship) => Piece::Ship(Ship {
+                    parent: map(ship.parent),
+                    sibling: map(ship.sibling),
+                    player: ship.player,
+                }),
+            };
+        }
+        // A pending sacrifice's ability goes with its color, as in `canonical_hash`
+        if let Special::Sacrifice(n, ability) = self.turn.special {
+            game.turn.special = Special::Sacrifice(n, Ability::for_color(perm[ability as usize]));
+        }
+        game.moving_piece = map_maybe(self.moving_piece);
+        game.wstar = map_maybe(self.wstar);
+        game.bstar = map_maybe(self.bstar);
+        game
+    }
+
+    // Method to list the homeworld selections open to the player setting up, the same
+    // ones `legal_moves` and `action_menu` offer. A second star the size of the first
+    // is allowed, weak as it is, since it builds a legal if lopsided homeworld.
+    pub fn setup_options(&self) -> Vec<Move> {
+        self.legal_moves_of_kind(MoveKind::Select)
+            .into_iter()
+            .map(|i| MOVES[i])
+            .collect()
+    }
+
+    // Method to verify the star/ship linkage of the board
+    pub fn check_invariants(&self) -> Result<(), InvariantError> {
+        let mut reachable = 0;
+        let mut ships = 0;
+        for key in KeyRange::all() {
+            match self.board[key] {
+                Piece::Bank => {}
+                Piece::Star { child } => {
+                    reachable += self.check_star_child(key, KeyMaybe::some(child))?;
+                }
+                Piece::BinaryFirst { child, sibling } => {
+                    if let Some(skey) = sibling.get() {
+                        if self.board[skey] != (Piece::BinarySecond { sibling: key }) {
+                            return Err(InvariantError::BinaryUnpaired { star: key });
+                        }
+                    }
+                    reachable += self.check_star_child(key, child)?;
+                }
+                Piece::BinarySecond { sibling } => match self.board[sibling] {
+                    Piece::BinaryFirst { sibling: s, .. } if s == KeyMaybe::some(key) => {}
+                    _ => return Err(InvariantError::BinaryUnpaired { star: key }),
+                },
+                Piece::Ship(ship) => {
+                    ships += 1;
+                    match self.board[ship.parent] {
+                        Piece::Star { .. } | Piece::BinaryFirst { .. } => {}
+                        _ => return Err(InvariantError::ShipParentNotStar { ship: key }),
+                    }
+                    // Walk the ring by hand; `sibling_iter` assumes it is well-formed
+                    let mut next = ship.sibling;
+                    let mut steps = 0;
+                    while next != key {
+                        match self.board[next] {
+                            Piece::Ship(sship) if sship.parent == ship.parent => {
+                                next = sship.sibling;
+                            }
+                            _ => return Err(InvariantError::ShipSiblingNotShip { ship: key }),
+                        }
+                        steps += 1;
+                        if steps > PIECE_COUNT {
+                            return Err(InvariantError::ShipSiblingCycle { ship: key });
+                        }
+                    }
+                }
+            }
+        }
+        // Every ship must hang off of its star's child
+        if reachable != ships {
+            let ship = KeyRange::all()
+                .find(|&key| match self.board[key] {
+                    Piece::Ship(ship) => {
+                        let child = match self.board[ship.parent] {
+                            Piece::Star { child } => KeyMaybe::some(child),
+                            Piece::BinaryFirst { child, .. } => child,
+                            _ => unreachable!(),
+                        };
+                        match child.get() {
+                            Some(ckey) => self.board.sibling_iter(ckey).all(|(_, skey)| skey != key),
+                            None => true,
+                        }
+                    }
+                    _ => false,
+                })
+                .unwrap();
+            return Err(InvariantError::ShipUnreachable { ship });
+        }
+        for (player, star) in [(Player::White, self.wstar), (Player::Black, self.bstar)] {
+            if let Some(skey) = star.get() {
+                match self.board[skey] {
+                    Piece::Star { .. } | Piece::BinaryFirst { .. } => {}
+                    _ => return Err(InvariantError::HomeworldNotStar { player }),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Method to check a star's child pointer, returning the number of ships in its system
+    fn check_star_child(&self, star: Key, child: KeyMaybe) -> Result<usize, InvariantError> {
+        let ckey = match child.get() {
+            Some(v) => v,
+            None => return Ok(0),
+        };
+        match self.board[ckey] {
+            Piece::Ship(ship) if ship.parent == star => {}
+            _ => return Err(InvariantError::StarChildNotShip { star }),
+        }
+        // Count the ring defensively; ring errors are reported per ship
+        let mut count = 1;
+        let mut next = ckey;
+        while let Piece::Ship(ship) = self.board[next] {
+            if ship.sibling == ckey || count > PIECE_COUNT {
+                break;
+            }
+            next = ship.sibling;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+// Function to play both players' homeworld setups from move strings
+#[cfg(test)]
+pub(crate) fn setup_game(white: [&str; 3], black: [&str; 3]) -> Game {
+    let mut game = Game::new();
+    for m in white.iter().chain(black.iter()) {
+        assert!(game.process_move(m.parse().unwrap()), "{}", m);
+    }
+    game
+}
+
+// Function to add a ship to a system directly, for positions that would take many
+// moves to reach
+#[cfg(test)]
+fn place_ship(game: &mut Game, key: u8, star: u8, player: Player) {
+    let (key, star) = (Key(key), Key(star));
+    assert!(game.board[key] == Piece::Bank);
+    let child = match game.board[star] {
+        Piece::Star { child } => Some(child),
+        Piece::BinaryFirst { child, .. } => child.get(),
+        _ => unreachable!(), // Ships only join systems through their first star
+    };
+    let sibling = match child {
+        Some(ckey) => {
+            let cship = match game.board[ckey] {
+                Piece::Ship(ship) => ship,
+                _ => unreachable!(),
+            };
+            game.board[ckey] = Piece::Ship(Ship {
+                sibling: key,
+                ..cship
+            });
+            cship.sibling
+        }
+        None => {
+            game.board[star] = match game.board[star] {
+                Piece::BinaryFirst { sibling, .. } => Piece::BinaryFirst {
+                    child: KeyMaybe::some(key),
+                    sibling,
+                },
+                _ => Piece::Star { child: key },
+            };
+            key
+        }
+    };
+    game.board[key] = Piece::Ship(Ship {
+        parent: star,
+        sibling,
+        player,
+    });
+    assert_eq!(game.check_invariants(), Ok(()));
+}
+
+#[test]
+fn test_legal_moves_of_kind() {
+    let game = Game::new();
+
+    // Only selections can build the first star
+    let selects = game.legal_moves_of_kind(MoveKind::Select);
+    assert_eq!(selects.len(), 12);
+    assert!(selects.iter().all(|&i| MOVES[i].kind() == MoveKind::Select));
+    assert!(game.legal_moves_of_kind(MoveKind::Attack).is_empty());
+    assert!(game.legal_moves_of_kind(MoveKind::Construct).is_empty());
+
+    // White: large green + small blue with a large yellow ship; Black mirrors
+    let game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select large green", "select medium blue", "select large yellow"],
+    );
+
+    // White can construct another yellow, but has nothing to attack
+    let constructs = game.legal_moves_of_kind(MoveKind::Construct);
+    assert_eq!(constructs.len(), 1);
+    assert!(game.legal_moves_of_kind(MoveKind::Attack).is_empty());
+    assert!(game.legal_moves_of_kind(MoveKind::Select).is_empty());
+
+    // Every move of a kind appears in the full list
+    let all = game.legal_moves();
+    for i in constructs {
+        assert!(all.contains(&i));
+    }
+}
+
+#[test]
+fn test_star_for() {
+    // The homeworld slot is the named player's, whoever is to move
+    let mut game = Game::new();
+    assert_eq!(game.to_move(), Player::White);
+    *game.star_for(Player::Black) = KeyMaybe::some(Key(12));
+    assert!(game.bstar == KeyMaybe::some(Key(12)));
+    assert!(game.wstar == KeyMaybe::none());
+    assert!(game.star_for_ref(Player::Black) == KeyMaybe::some(Key(12)));
+
+    // Selecting a piece the stash has run out of is refused rather than panicking
+    let mut game = Game::new();
+    for m in ["select large green", "select small blue", "select large green"] {
+        assert!(game.process_move(m.parse().unwrap()));
+    }
+    assert!(game.process_move(Move::Select(Size::Large, Color::Green)));
+    assert!(!game.process_move(Move::Select(Size::Large, Color::Green)));
+    assert_eq!(game.bank_count(Size::Large, Color::Green), 0);
+}
+
+#[test]
+fn test_setup_options() {
+    let mut game = Game::new();
+    let mut expected = Vec::new();
+    for size in Size::list() {
+        for color in Color::list() {
+            expected.push(Move::Select(size, color));
+        }
+    }
+    assert_eq!(game.setup_options(), expected);
+
+    // The second star may repeat the first star's size, and the move table agrees
+    assert!(game.process_move("select large green".parse().unwrap()));
+    let options = game.setup_options();
+    assert_eq!(options.len(), 12);
+    assert_eq!(game.legal_moves().len(), 12);
+    assert_eq!(game.action_menu(), vec![(MoveKind::Select, options)]);
+
+    // Any size is fine for the ship
+    assert!(game.process_move("select small blue".parse().unwrap()));
+    assert_eq!(game.setup_options().len(), 12);
+
+    // Exhausted stash entries are not offered
+    assert!(game.process_move("select small blue".parse().unwrap()));
+    assert!(game.process_move("select small blue".parse().unwrap()));
+    assert_eq!(game.setup_options().len(), 11);
+    assert!(game.process_move("select large yellow".parse().unwrap()));
+    let options = game.setup_options();
+    assert_eq!(options.len(), 11);
+    assert!(!options.contains(&Move::Select(Size::Small, Color::Blue)));
+
+    // Nothing to select once the setup is over
+    assert!(game.process_move("select large red".parse().unwrap()));
+    assert!(game.setup_options().is_empty());
+}
+
+#[test]
+fn test_encode_move() {
+    // Every move of every verb survives the round trip, each with its own encoding
+    let mut seen = std::collections::HashSet::new();
+    for &m in MOVES.iter() {
+        let v = encode_move(m);
+        assert_eq!(decode_move(v), Some(m), "{}", m);
+        assert!(seen.insert(v), "{}", m);
+    }
+
+    // Keys past the last piece
+    assert_eq!(decode_move(encode_move(Move::Attack(Key(35))) + 1), None);
+    assert_eq!(decode_move(encode_move(Move::Catastrophe(Key(35))) + 1), None);
+    assert_eq!(decode_move(encode_move(Move::MoveInit(Key(35))) + (1 << 7)), None);
+    // Both halves of a movement at once, or neither
+    assert_eq!(decode_move(0b0010_0000_1000_0011), None);
+    assert_eq!(decode_move(0b0011_1111_1111_1111), None);
+    // A size of 3 and a color that doesn't exist
+    assert_eq!(decode_move(0b1010_0001_1000_0000), None);
+    assert_eq!(decode_move(0b0110_0000_1000_0001), None);
+}
+
+#[test]
+fn test_move_conversions() {
+    // A server can take a move token in either form through the same generic path
+    fn parse<T>(token: T) -> Option<Move>
+    where
+        Move: TryFrom<T>,
+    {
+        Move::try_from(token).ok()
+    }
+
+    let m = Move::Transform(Key(9), Color::Green);
+    let bits: u16 = m.into();
+    assert_eq!(bits, encode_move(m));
+    assert_eq!(parse(bits), Some(m));
+    assert_eq!(parse("transform 9 green"), Some(m));
+    assert_eq!(u16::from(Move::Pass), encode_move(Move::Pass));
+
+    // Bad tokens are refused rather than turned into a malformed move
+    assert_eq!(Move::try_from(0xFFFF), Err(WireError::BadEncoding(0xFFFF)));
+    let past_end = encode_move(Move::Attack(Key(35))) + 1;
+    assert_eq!(Move::try_from(past_end), Err(WireError::BadEncoding(past_end)));
+    assert_eq!(parse("transform 9 purple"), None);
+    assert_eq!(parse(""), None);
+}
+
+#[test]
+fn test_make_key() {
+    let mut keys = Vec::new();
+    for color in Color::list() {
+        for size in Size::list() {
+            for index in 0..COPY_COUNT as u8 {
+                let key = make_key(color, size, index).unwrap();
+                assert_eq!(key_parts(key), (color, size, index));
+                keys.push(key);
+            }
+            assert_eq!(make_key(color, size, COPY_COUNT as u8), None);
+        }
+    }
+    // Every slot on the board, each once, in key order
+    assert_eq!(keys, KeyRange::all().collect::<Vec<_>>());
+    assert_eq!(keys.len(), PIECE_COUNT);
+
+    // Built keys name pieces in moves as parsed ones do
+    let key = make_key(Color::Yellow, Size::Large, 0).unwrap();
+    assert_eq!(Move::Construct(key), "construct 15".parse().unwrap());
+}
+
+#[test]
+fn test_encode_history() {
+    // A whole game, from the homeworld setups to White's winning catastrophe
+    let moves: Vec<Move> = [
+        "select large green",
+        "select small blue",
+        "select large yellow",
+        "select medium yellow",
+        "select medium green",
+        "select small yellow",
+        "construct 15",
+        "pass",
+        "construct 10",
+        "pass",
+        "moveinit 15",
+        "movefinish 12",
+        "pass",
+        "moveinit 10",
+        "movefinish 12",
+    ]
+    .iter()
+    .map(|m| m.parse().unwrap())
+    .collect();
+    let game = Game::from_moves(&moves).unwrap();
+    assert_eq!(game.game_result(), GameResult::Win(Player::White));
+
+    let bytes = encode_history(&moves);
+    assert_eq!(bytes.len(), 1 + 2 * moves.len());
+    assert_eq!(bytes[0], HISTORY_VERSION);
+    assert_eq!(decode_history(&bytes), Ok(moves));
+    assert_eq!(decode_history(&[HISTORY_VERSION]), Ok(Vec::new()));
+
+    // A dangling byte, and a move naming a key past the last piece
+    assert_eq!(decode_history(&bytes[..4]), Err(DecodeError::OddLength));
+    let mut bad = bytes.clone();
+    let bits = encode_move(Move::Attack(Key(35))) + 1;
+    bad[5..7].copy_from_slice(&bits.to_be_bytes());
+    assert_eq!(decode_history(&bad), Err(DecodeError::BadMove { index: 2, bits }));
+}
+
+#[test]
+fn test_history_version() {
+    let bytes = encode_history(&[Move::Pass]);
+
+    // Data from a later format is refused whole, never read as moves
+    for version in [0, HISTORY_VERSION + 1, 0xFF] {
+        let mut future = bytes.clone();
+        future[0] = version;
+        let error = DecodeError::Version(VersionError::Unknown(version));
+        assert_eq!(decode_history(&future), Err(error));
+    }
+    assert_eq!(decode_history(&[]), Err(DecodeError::Version(VersionError::Missing)));
+}
+
+#[test]
+fn test_system_list() {
+    // A midgame position survives the trip out and back
+    let mut game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    for m in ["construct 15", "pass", "moveinit 10", "movefinish 12"] {
+        assert!(game.process_move(m.parse().unwrap()), "{}", m);
+    }
+    let text = game.to_system_list();
+    assert_eq!(
+        text,
+        "black: y2 g2 | y1 | y1\nwhite: g3 b1 | y3 |\nturn: black\n"
+    );
+    let copy = Game::from_system_list(&text).unwrap();
+    assert_eq!(copy.check_invariants(), Ok(()));
+    assert_eq!(copy.to_system_list(), text);
+    // Identical pieces may trade keys, so only counts are compared
+    assert_eq!(copy.legal_moves().len(), game.legal_moves().len());
+    assert_eq!(copy.canonical_hash(), game.canonical_hash());
+
+    // A colony of its own, with a ship for each side
+    let homes = "white: g3 b1 | y3 |\nblack: y2 g2 | | y1\n";
+    let game = Game::from_system_list(&format!("{}x: r1 | g1 | b2\nturn: white", homes)).unwrap();
+    assert_eq!(game.check_invariants(), Ok(()));
+    assert_eq!(game.systems().len(), 3);
+    assert_eq!(game.material_balance(Player::White), 1);
+
+    // Each line past the homeworlds is a third system, a colony unless it's named as a home
+    let error = |s: &str| Game::from_system_list(s).err();
+    let third = |system: &str| error(&format!("{}{}\nturn: white", homes, system));
+    assert_eq!(error(homes), Some(SystemListError::NoTurn));
+    assert_eq!(error("white: g3 | y3\n"), Some(SystemListError::Syntax { line: 1 }));
+    assert_eq!(error("white: g4 | y3 |\n"), Some(SystemListError::BadPiece { line: 1 }));
+    assert_eq!(third("x: r1 | |"), Some(SystemListError::BadSystem { line: 3 }));
+    assert_eq!(third("x: r1 r2 | g1 |"), Some(SystemListError::BadSystem { line: 3 }));
+    assert_eq!(third("x: y3 | y3 y3 |"), Some(SystemListError::OutOfPieces { line: 3 }));
+    assert_eq!(third("white: r3 | r1 |"), Some(SystemListError::Homeworld(Player::White)));
+    assert_eq!(
+        error("white: g3 b1 | y3 |\nturn: white"),
+        Some(SystemListError::Homeworld(Player::Black))
+    );
+}
+
+#[test]
+fn test_diff() {
+    let game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    assert!(diff(&game, &game).is_empty());
+
+    // A build fills one slot
+    let mut built = game.clone();
+    assert!(built.process_move("construct 15".parse().unwrap()));
+    let ship = |key: u8, star: u8| PieceInfo::Ship {
+        size: Key(key).size(),
+        color: Key(key).color(),
+        owner: Player::White,
+        system: SystemId(Key(star)),
+    };
+    assert_eq!(diff(&game, &built).changes, vec![(10, PieceInfo::Bank, ship(10, 24))]);
+
+    // A movement changes only the mover's system, and a half-finished one nothing at all
+    let mut moved = built.clone();
+    assert!(moved.process_move(Move::Pass));
+    assert!(moved.process_move("moveinit 15".parse().unwrap()));
+    assert!(diff(&built, &moved).is_empty());
+    assert!(moved.process_move("movefinish 12".parse().unwrap()));
+    assert_eq!(diff(&built, &moved).changes, vec![(15, ship(15, 24), ship(15, 12))]);
+}
+
+#[test]
+fn test_try_apply_wire() {
+    let mut game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    let construct = encode_move("construct 15".parse().unwrap()).to_be_bytes();
+    assert_eq!(game.try_apply_wire(&construct[..1]), Err(WireError::BadLength(1)));
+    assert_eq!(game.try_apply_wire(&[]), Err(WireError::BadLength(0)));
+    assert_eq!(game.try_apply_wire(&[0xFF, 0xFF]), Err(WireError::BadEncoding(0xFFFF)));
+    assert_eq!(game.try_apply_wire(&construct), Ok(()));
+    // Black has no green to build with
+    assert_eq!(
+        game.try_apply_wire(&construct),
+        Err(WireError::Illegal(IllegalReason::Rejected))
+    );
+    let select = encode_move(Move::Select(Size::Small, Color::Red)).to_be_bytes();
+    assert_eq!(
+        game.try_apply_wire(&select),
+        Err(WireError::Illegal(IllegalReason::WrongPhase))
+    );
+
+    // Black's homeworld is empty, so White has already won
+    let mut over = Game::from_system_list(
+        "white: g3 b1 | y3 |\nblack: y2 | |\nturn: black",
+    )
+    .unwrap();
+    let pass = encode_move(Move::Pass).to_be_bytes();
+    assert_eq!(over.try_apply_wire(&pass), Err(WireError::GameOver));
+
+    // Arbitrary bytes in all sorts of positions never panic, and refusals change nothing
+    for seed in 0..200 {
+        let mut game = crate::fuzz::apply_random_game(seed, (seed % 120) as usize);
+        for step in 0..500 {
+            let bytes = wyhash64(seed, step).to_be_bytes();
+            let before = game.clone();
+            if game.try_apply_wire(&bytes[..2]).is_err() {
+                assert_eq!(game.hash(), before.hash());
+                assert_eq!(game.ply(), before.ply());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_dimensions() {
+    // The standard stash, and a move table entry for every slot of the policy head
+    assert_eq!(PIECE_COUNT, 36);
+    assert_eq!(MOVE_COUNT, 337);
+    assert_eq!(MOVES.len(), MOVE_COUNT);
+    assert_eq!(Game::new().legal_moves().len(), SIZE_COUNT * COLOR_COUNT);
+
+    // Every copy of a piece shares its size and color, and keys run color by color
+    for key in KeyRange::all() {
+        let first = KeyRange::with_color_and_size(key.color(), key.size()).next().unwrap();
+        assert_eq!(key.0 as usize - first.0 as usize, key.0 as usize % COPY_COUNT);
+    }
+    assert!(Game::new().piece_at(PIECE_COUNT as u8 - 1).is_some());
+    assert!(Game::new().piece_at(PIECE_COUNT as u8).is_none());
+}
+
+#[test]
+fn test_move_table() {
+    let table = move_table();
+    assert_eq!(table.len(), MOVE_COUNT);
+    let names: std::collections::HashSet<&str> = table.iter().map(|(_, name, _)| name.as_str()).collect();
+    assert_eq!(names.len(), MOVE_COUNT);
+
+    // Each entry names the move its index plays, whether by index or by text
+    let game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    for (i, name, kind) in table {
+        let m: Move = name.parse().unwrap();
+        assert_eq!(m, MOVES[i]);
+        assert_eq!(m.kind(), kind);
+        let (mut by_index, mut by_text) = (game.clone(), game.clone());
+        assert_eq!(by_index.process_move_idx(i), by_text.process_move(m), "{}", name);
+        assert_eq!(by_index.hash(), by_text.hash());
+    }
+    assert_eq!(move_table()[MOVE_COUNT - 1].1, "pass");
+}
+
+#[test]
+fn test_permute_colors() {
+    use Color::*;
+    let swap = [Blue, Yellow, Green, Red];
+    let cycle = [Yellow, Green, Blue, Red];
+    let inverse = [Blue, Red, Yellow, Green];
+
+    // White can use neither red nor blue; Black has both, one from its star and one from
+    // a ship, so swapping the two gives either side the same choices
+    let mut game = Game::from_system_list(
+        "white: g3 y1 | y2 g1 |
+         black: r2 | | b3 r1
+         turn: white",
+    )
+    .unwrap();
+    for _ in 0..2 {
+        let swapped = game.permute_colors(swap);
+        assert_eq!(swapped.check_invariants(), Ok(()));
+        assert_eq!(swapped.legal_moves().len(), game.legal_moves().len());
+        assert!(swapped.to_system_list().contains("black: b2 |  | r3 b1"));
+        assert_eq!(swapped.permute_colors(swap).hash(), game.hash());
+        assert!(game.process_move(Move::Pass));
+    }
+
+    // Relabeling and relabeling back restores the position, moves under way included
+    let mut game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    assert!(game.process_move("moveinit 15".parse().unwrap()));
+    let there = game.permute_colors(cycle);
+    assert_eq!(there.check_invariants(), Ok(()));
+    let back = there.permute_colors(inverse);
+    assert_eq!(back.hash(), game.hash());
+    assert_eq!(back.to_system_list(), game.to_system_list());
+    assert_eq!(back.legal_moves(), game.legal_moves());
+
+    // Every color is still as good as any other while homeworlds are being chosen
+    let mut game = Game::new();
+    assert!(game.process_move("select large green".parse().unwrap()));
+    assert_eq!(game.permute_colors(cycle).legal_moves().len(), game.legal_moves().len());
+
+    // Mid-sacrifice, the attacks still owed become the color red is relabeled to
+    let mut game = Game::from_system_list(
+        "white: g3 b1 | y1 r2 |\nblack: y2 b2 | | g2 r1\nmid: y1 | y3 g1 | b3\nturn: white",
+    )
+    .unwrap();
+    let red = make_key(Color::Red, Size::Medium, 0).unwrap();
+    assert!(game.process_move(Move::Sacrifice(red)));
+    assert!(matches!(game.turn.special, Special::Sacrifice(2, Ability::Attack)));
+    let there = game.permute_colors(cycle);
+    assert!(matches!(there.turn.special, Special::Sacrifice(2, Ability::Move)));
+    assert_eq!(there.canonical_hash(), game.canonical_hash());
+    assert_eq!(there.permute_colors(inverse).hash(), game.hash());
+}
+
+#[test]
+fn test_piece_at() {
+    let game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    // White's binary star and large yellow ship
+    let star = PieceInfo::Star {
+        size: Size::Large,
+        color: Color::Green,
+    };
+    assert_eq!(game.piece_at(24), Some(star));
+    let star = PieceInfo::Star {
+        size: Size::Small,
+        color: Color::Blue,
+    };
+    assert_eq!(game.piece_at(27), Some(star));
+    let ship = PieceInfo::Ship {
+        size: Size::Large,
+        color: Color::Yellow,
+        owner: Player::White,
+        system: SystemId(Key(24)),
+    };
+    assert_eq!(game.piece_at(15), Some(ship));
+
+    // Black's ship belongs to the first star of Black's binary
+    let ship = PieceInfo::Ship {
+        size: Size::Small,
+        color: Color::Yellow,
+        owner: Player::Black,
+        system: SystemId(Key(12)),
+    };
+    assert_eq!(game.piece_at(9), Some(ship));
+    let star = PieceInfo::Star {
+        size: Size::Medium,
+        color: Color::Yellow,
+    };
+    assert_eq!(game.piece_at(SystemId(Key(12)).star()), Some(star));
+
+    assert_eq!(game.piece_at(0), Some(PieceInfo::Bank));
+    assert_eq!(game.piece_at(36), None);
+}
+
+#[test]
+fn test_canonical_hash() {
+    // The same homeworlds with the players' roles swapped; the large yellow ships
+    // also trade keys, since whoever selects first takes the lower one
+    let a = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium red", "select small green", "select large yellow"],
+    );
+    let mut b = setup_game(
+        ["select medium red", "select small green", "select large yellow"],
+        ["select large green", "select small blue", "select large yellow"],
+    );
+    assert!(b.process_move(Move::Pass));
+    assert_eq!(a.to_move(), Player::White);
+    assert_eq!(b.to_move(), Player::Black);
+    assert_ne!(a.hash(), b.hash());
+    assert_eq!(a.canonical_hash(), b.canonical_hash());
+
+    // Play diverges from there
+    let mut c = a.clone();
+    assert!(c.process_move("construct 15".parse().unwrap()));
+    assert!(c.process_move(Move::Pass));
+    assert_ne!(a.canonical_hash(), c.canonical_hash());
+
+    // Swapping red and yellow everywhere is a symmetry, but swapping them only in ships
+    // isn't
+    let d = setup_game(
+        ["select large green", "select small blue", "select large red"],
+        ["select medium yellow", "select small green", "select large red"],
+    );
+    assert_eq!(a.canonical_hash(), d.canonical_hash());
+    let e = setup_game(
+        ["select large green", "select small blue", "select large red"],
+        ["select medium red", "select small green", "select large red"],
+    );
+    assert_ne!(a.canonical_hash(), e.canonical_hash());
+}
+
+#[test]
+fn test_process_move_checked() {
+    let mut game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    let index = |m: Move| MOVES.iter().position(|&v| v == m).unwrap();
+
+    // Refusals say why and leave the game alone
+    let hash = game.hash();
+    assert_eq!(game.process_move_checked(MOVE_COUNT), Err(IllegalReason::UnknownMove));
+    let select = index(Move::Select(Size::Small, Color::Red));
+    assert_eq!(game.process_move_checked(select), Err(IllegalReason::WrongPhase));
+    let finish = index(Move::MoveFinish(Key(12)));
+    assert_eq!(game.process_move_checked(finish), Err(IllegalReason::WrongPhase));
+    let attack = index(Move::Attack(Key(9)));
+    assert_eq!(game.process_move_checked(attack), Err(IllegalReason::Rejected));
+    assert_eq!(game.hash(), hash);
+
+    // A construction takes a piece from the bank and ends the turn
+    let effects = game.process_move_checked(index(Move::Construct(Key(15))));
+    assert_eq!(
+        effects,
+        Ok(MoveEffects {
+            placed: vec![10],
+            removed: vec![],
+            turn_ended: true,
+            catastrophes: 0,
+            result: GameResult::Ongoing,
+        })
+    );
+
+    // A yellow sacrifice opens only movement, and doesn't end the turn
+    assert_eq!(game.pending_actions(), None);
+    let effects = game.process_move_checked(index(Move::Sacrifice(Key(9)))).unwrap();
+    assert_eq!(effects.removed, vec![9]);
+    assert!(!effects.turn_ended);
+    assert_eq!(game.pending_actions(), Some((Ability::Move, 1)));
+    let construct = index(Move::Construct(Key(15)));
+    assert_eq!(game.process_move_checked(construct), Err(IllegalReason::WrongPhase));
+
+    // Black's homeworld is empty once the sacrifice is over
+    let effects = game.process_move_checked(index(Move::Pass)).unwrap();
+    assert_eq!(effects.result, GameResult::Win(Player::White));
+    assert_eq!(game.process_move_checked(construct), Err(IllegalReason::GameOver));
+}
+
+#[test]
+fn test_catastrophe_preview() {
+    let mut game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    game.set_catastrophe_mode(CatastropheMode::Manual);
+    // Three White yellows join Black's yellow star and ship
+    for m in [
+        "construct 15",
+        "pass",
+        "construct 10",
+        "pass",
+        "moveinit 15",
+        "movefinish 12",
+        "pass",
+        "moveinit 10",
+        "movefinish 12",
+    ] {
+        assert!(game.process_move(m.parse().unwrap()), "{}", m);
+    }
+
+    // The yellow star and ships go; the green star keeps the system alive
+    let hash = game.hash();
+    let preview = game.catastrophe_preview(9);
+    assert_eq!(preview, Some(vec![9, 10, 12, 15]));
+    assert_eq!(game.hash(), hash);
+    let star = PieceInfo::Star {
+        size: Size::Medium,
+        color: Color::Green,
+    };
+    assert_eq!(game.piece_at(21), Some(star));
+
+    // Only the lowest yellow ship names it, and nothing else is overpopulated
+    assert_eq!(game.catastrophe_preview(10), None);
+    assert_eq!(game.catastrophe_preview(11), None);
+    assert_eq!(game.catastrophe_preview(21), None);
+    assert_eq!(game.catastrophe_preview(36), None);
+
+    // The preview is exactly what the catastrophe clears
+    let occupied = |game: &Game| -> Vec<u8> {
+        (0..36)
+            .filter(|&k| game.piece_at(k) != Some(PieceInfo::Bank))
+            .collect()
+    };
+    let before = occupied(&game);
+    assert!(game.process_move(Move::Catastrophe(Key(9))));
+    let after = occupied(&game);
+    let cleared: Vec<u8> = before.into_iter().filter(|k| !after.contains(k)).collect();
+    assert_eq!(Some(cleared), preview);
+}
+
+#[test]
+fn test_ply() {
+    let mut game = Game::new();
+    assert_eq!(game.ply(), 0);
+    assert_eq!(game.move_number(), 1);
+
+    // Rejected moves don't count
+    assert!(!game.process_move("construct 15".parse().unwrap()));
+    assert_eq!(game.ply(), 0);
+
+    // Setup selections, both halves of a movement and passes all count
+    let mut game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    assert_eq!(game.ply(), 6);
+    for m in ["construct 15", "pass", "moveinit 15", "movefinish 12"] {
+        assert!(game.process_move(m.parse().unwrap()), "{}", m);
+    }
+    assert!(!game.process_move("movefinish 12".parse().unwrap()));
+    assert_eq!(game.ply(), 10);
+    assert_eq!(game.move_number(), 11);
+}
+
+#[test]
+fn test_apply_moves() {
+    let index = |m: &str| {
+        let m: Move = m.parse().unwrap();
+        MOVES.iter().position(|&v| v == m).unwrap()
+    };
+    let setup: Vec<usize> = [
+        "select large green",
+        "select small blue",
+        "select large yellow",
+        "select medium yellow",
+        "select medium green",
+        "select small yellow",
+    ]
+    .iter()
+    .map(|m| index(m))
+    .collect();
+    let mut game = Game::new();
+    assert_eq!(game.apply_moves(&setup), Ok(()));
+    assert_eq!(game.ply(), 6);
+
+    // The second construction must name the lower yellow, so the batch stops there
+    let moves = [index("construct 15"), index("pass"), index("construct 15"), index("pass")];
+    assert_eq!(game.apply_moves(&moves), Err((2, IllegalReason::Rejected)));
+    assert_eq!(game.ply(), 8);
+    assert_eq!(game.to_move(), Player::White);
+
+    assert_eq!(game.apply_moves(&[MOVE_COUNT]), Err((0, IllegalReason::UnknownMove)));
+    assert_eq!(game.apply_moves(&[]), Ok(()));
+
+    // Leniently, Black building off White's ship and an index past the table are
+    // passed over
+    let moves = [
+        index("construct 10"),
+        index("construct 15"),
+        MOVE_COUNT,
+        index("pass"),
+        index("pass"),
+    ];
+    let mut skipped = Vec::new();
+    assert_eq!(game.apply_moves_lenient(&moves, |i| skipped.push(i)), 3);
+    assert_eq!(skipped, [1, 2]);
+    assert_eq!(game.ply(), 11);
+    assert_eq!(game.to_move(), Player::Black);
+}
+
+#[test]
+fn test_construct_allocation() {
+    let mut game = setup_game(
+        ["select large green", "select small blue", "select medium red"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    // Keep the growing red fleet from collapsing at the end of each turn
+    game.set_catastrophe_mode(CatastropheMode::Manual);
+    let red = |size| PieceInfo::Ship {
+        size,
+        color: Color::Red,
+        owner: Player::White,
+        system: SystemId(Key(24)),
+    };
+
+    // The small reds go first
+    assert!(game.process_move("construct 3".parse().unwrap()));
+    assert!(game.process_move(Move::Pass));
+    for _ in 0..2 {
+        assert!(game.process_move("construct 0".parse().unwrap()));
+        assert!(game.process_move(Move::Pass));
+    }
+    assert_eq!(game.piece_at(2), Some(red(Size::Small)));
+    assert_eq!(game.piece_at(4), Some(PieceInfo::Bank));
+
+    // With the small reds exhausted, the free medium red comes next
+    assert!(game.process_move("construct 0".parse().unwrap()));
+    assert!(game.process_move(Move::Pass));
+    assert_eq!(game.piece_at(4), Some(red(Size::Medium)));
+    assert_eq!(game.piece_at(5), Some(PieceInfo::Bank));
+
+    // Then the rest, until every red is in play
+    for _ in 0..4 {
+        assert!(game.process_move("construct 0".parse().unwrap()));
+        assert!(game.process_move(Move::Pass));
+    }
+    assert_eq!(game.piece_at(8), Some(red(Size::Large)));
+    assert!(!game.process_move("construct 0".parse().unwrap()));
+    assert!(game.legal_moves_of_kind(MoveKind::Construct).is_empty());
+}
+
+#[test]
+fn test_pending_actions() {
+    let mut game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    assert!(game.process_move("construct 15".parse().unwrap()));
+    assert!(game.process_move(Move::Pass));
+    assert_eq!(game.pending_actions(), None);
+
+    // A large yellow grants three movements, counted down as they land
+    assert!(game.process_move("sacrifice 15".parse().unwrap()));
+    assert_eq!(game.pending_actions(), Some((Ability::Move, 3)));
+    assert!(game.process_move("moveinit 10".parse().unwrap()));
+    assert_eq!(game.pending_actions(), Some((Ability::Move, 3)));
+    assert!(game.process_move("movefinish 12".parse().unwrap()));
+    assert_eq!(game.pending_actions(), Some((Ability::Move, 2)));
+
+    // Passing forfeits the rest
+    assert!(game.process_move(Move::Pass));
+    assert_eq!(game.pending_actions(), None);
+}
+
+#[test]
+fn test_reachability() {
+    // Large-small and medium homeworlds share no size
+    let game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    assert_eq!(game.systems(), vec![SystemId(Key(12)), SystemId(Key(24))]);
+    assert_eq!(game.reachability(), vec![(SystemId(Key(12)), SystemId(Key(24)))]);
+
+    // A shared large star cuts the route, whichever star of the binary it is
+    let game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select large red", "select small yellow"],
+    );
+    assert_eq!(game.reachability(), vec![]);
+    assert!(game.legal_moves_of_kind(MoveKind::Move).iter().all(|&i| {
+        let mut game = game.clone();
+        game.process_move_idx(i);
+        game.legal_moves_of_kind(MoveKind::Move).is_empty()
+    }));
+}
+
+#[test]
+fn test_system_contents() {
+    let game = Game::from_system_list(
+        "white: g3 b1 | y3 r1 |\nblack: y2 | g1 | b2\nc1: r1 | | y1 g2\nturn: white",
+    )
+    .unwrap();
+    let white = game.homeworld(Player::White).unwrap();
+    let black = game.homeworld(Player::Black).unwrap();
+    let colony = SystemId::new(make_key(Color::Red, Size::Small, 1).unwrap().0).unwrap();
+    assert_eq!(game.systems(), vec![colony, black, white]);
+    assert_eq!(SystemId::new(white.star()), Some(white));
+    assert_eq!(SystemId::new(PIECE_COUNT as u8), None);
+
+    // The contents rebuild the same position
+    let specs: Vec<SystemSpec> = [white, black, colony]
+        .iter()
+        .map(|&system| game.system_contents(system).unwrap())
+        .collect();
+    assert_eq!(specs[0].stars, vec![(Size::Large, Color::Green), (Size::Small, Color::Blue)]);
+    let ships = vec![
+        (Size::Small, Color::Green, Player::White),
+        (Size::Medium, Color::Blue, Player::Black),
+    ];
+    assert_eq!(specs[1].ships, ships);
+    let ids = SystemSpec::ids(&specs);
+    let rebuilt = Game::new_from_board(&specs, ids[0].unwrap(), ids[1].unwrap(), Player::White);
+    let rebuilt = rebuilt.unwrap();
+    assert_eq!(rebuilt.hash_up_to_copies(), game.hash_up_to_copies());
+
+    // The medium homeworld reaches both the others, which share the small size
+    assert_eq!(game.move_destinations(black), vec![colony, white]);
+    assert_eq!(game.move_destinations(colony), vec![black]);
+    let bank = SystemId::new(make_key(Color::Red, Size::Large, 0).unwrap().0).unwrap();
+    assert_eq!(game.system_contents(bank), None);
+    assert_eq!(game.move_destinations(bank), vec![]);
+}
+
+#[test]
+fn test_pass_during_setup() {
+    // Nobody may pass until both homeworlds are built, at any step of either setup
+    let mut game = Game::new();
+    for m in [
+        "select large green",
+        "select small blue",
+        "select large yellow",
+        "select medium red",
+        "select medium green",
+        "select small blue",
+    ] {
+        let before = game.hash();
+        assert!(!game.process_move(Move::Pass), "before {}", m);
+        assert!(game.legal_moves_of_kind(MoveKind::Pass).is_empty());
+        assert_eq!(game.hash(), before);
+        assert!(game.process_move(m.parse().unwrap()), "{}", m);
+    }
+    assert!(game.process_move(Move::Pass));
+    assert_eq!(game.to_move(), Player::Black);
+}
+
+#[test]
+fn test_action_menu() {
+    // Only selections during setup
+    let mut game = Game::new();
+    let menu = game.action_menu();
+    assert_eq!(menu.len(), 1);
+    assert_eq!(menu[0].0, MoveKind::Select);
+    assert_eq!(menu[0].1, game.setup_options());
+    assert!(!game.process_move(Move::Pass));
+
+    for m in [
+        "select large green",
+        "select small blue",
+        "select large yellow",
+        "select medium yellow",
+        "select medium green",
+        "select small yellow",
+    ] {
+        assert!(game.process_move(m.parse().unwrap()), "{}", m);
+    }
+    let kinds: Vec<MoveKind> = game.action_menu().iter().map(|(k, _)| *k).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            MoveKind::Construct,
+            MoveKind::Sacrifice,
+            MoveKind::Move,
+            MoveKind::Pass
+        ]
+    );
+
+    // A sacrifice narrows it to the granted ability, plus passing
+    assert!(game.process_move("construct 15".parse().unwrap()));
+    assert!(game.process_move(Move::Pass));
+    assert!(game.process_move("sacrifice 10".parse().unwrap()));
+    let menu = game.action_menu();
+    let kinds: Vec<MoveKind> = menu.iter().map(|(k, _)| *k).collect();
+    assert_eq!(kinds, vec![MoveKind::Move, MoveKind::Pass]);
+    assert_eq!(menu[0].1, vec![Move::MoveInit(Key(15))]);
+}
+
+#[test]
+fn test_catastrophes_resolve_once() {
+    let mut game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    let index = |m: &str| {
+        let m: Move = m.parse().unwrap();
+        MOVES.iter().position(|&v| v == m).unwrap()
+    };
+    for m in ["construct 15", "pass", "construct 10", "pass", "moveinit 15"] {
+        assert!(game.process_move_checked(index(m)).is_ok(), "{}", m);
+    }
+
+    // A turn that ends with three yellows in Black's homeworld collapses nothing
+    let effects = game.process_move_checked(index("movefinish 12")).unwrap();
+    assert!(effects.turn_ended);
+    assert_eq!(effects.catastrophes, 0);
+    assert!(game.process_move_checked(index("pass")).is_ok());
+    assert!(game.process_move_checked(index("moveinit 10")).is_ok());
+
+    // The fourth collapses once, at the end of the turn that brought it
+    let effects = game.process_move_checked(index("movefinish 12")).unwrap();
+    assert!(effects.turn_ended);
+    assert_eq!(effects.catastrophes, 1);
+    assert_eq!(effects.removed, vec![9, 10, 12, 15]);
+
+    // A pass is a turn boundary too, and resolves a standing overpopulation once
+    let mut game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    game.set_catastrophe_mode(CatastropheMode::Manual);
+    for m in ["construct 15", "pass", "construct 10", "pass", "moveinit 15", "movefinish 12"] {
+        assert!(game.process_move_checked(index(m)).is_ok(), "{}", m);
+    }
+    assert!(game.process_move_checked(index("pass")).is_ok());
+    assert!(game.process_move_checked(index("moveinit 10")).is_ok());
+    assert!(game.process_move_checked(index("movefinish 12")).is_ok());
+    assert_eq!(game.overpopulations(), vec![Key(9)]);
+    game.set_catastrophe_mode(CatastropheMode::Auto);
+    let effects = game.process_move_checked(index("pass")).unwrap();
+    assert_eq!(effects.catastrophes, 1);
+    assert!(game.overpopulations().is_empty());
+}
+
+#[test]
+fn test_from_moves() {
+    let moves: Vec<Move> = ["select large green", "select small blue", "construct 15"]
+        .iter()
+        .map(|m| m.parse().unwrap())
+        .collect();
+    assert_eq!(
+        Game::from_moves(&moves).err(),
+        Some((2, IllegalReason::WrongPhase))
+    );
+
+    // Moves outside the move table are refused by position too
+    let moves = [Move::Select(Size::Large, Color::Green), Move::Attack(Key(36))];
+    assert_eq!(
+        Game::from_moves(&moves).err(),
+        Some((1, IllegalReason::UnknownMove))
+    );
+    assert_eq!(Game::from_moves(&[]).unwrap().ply(), 0);
+}
+
+#[test]
+fn test_render_for() {
+    let mut game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    assert!(game.process_move("construct 15".parse().unwrap()));
+    let full = "Black to move\n\
+        Black home: medium yellow + medium green | Black small yellow\n\
+        White home: large green + small blue | White small yellow, White large yellow\n\
+        Bank: small red x3, medium red x3, large red x3, small yellow x1, medium yellow x2, \
+        large yellow x2, small green x3, medium green x2, large green x2, small blue x2, \
+        medium blue x3, large blue x3\n";
+    assert_eq!(game.render(), full);
+    for viewer in [Player::White, Player::Black] {
+        assert_eq!(game.render_for(viewer, Fog::Open), full);
+    }
+
+    // Hiding the bank is the same for both players
+    let hidden = game.render_for(Player::White, Fog::HideBank);
+    assert_eq!(hidden, game.render_for(Player::Black, Fog::HideBank));
+    assert!(hidden.ends_with("Bank: hidden\n"));
+    assert_eq!(hidden.lines().count(), full.lines().count());
+    assert_eq!(game.render(), full);
+
+    // Hiding sizes only covers the opponent's ships
+    let white = game.render_for(Player::White, Fog::HideOpponentSizes);
+    assert!(white.contains("Black home: medium yellow + medium green | Black ? yellow\n"));
+    assert!(white.contains("White small yellow, White large yellow\n"));
+    let black = game.render_for(Player::Black, Fog::HideOpponentSizes);
+    assert!(black.contains("| Black small yellow\n"));
+    assert!(black.contains("White ? yellow, White ? yellow\n"));
+}
+
+#[test]
+fn test_material_balance() {
+    // Mirrored setups are even
+    let mut game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select large yellow"],
+    );
+    assert_eq!(game.material_balance(Player::White), 0);
+    assert_eq!(game.material_balance(Player::Black), 0);
+
+    // A small yellow built for White
+    assert!(game.process_move("construct 15".parse().unwrap()));
+    assert_eq!(game.material_balance(Player::White), 1);
+    assert_eq!(game.material_balance(Player::Black), -1);
+}
+
+#[test]
+fn test_no_select_after_setup() {
+    let mut game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    for m in ["construct 15", "pass", "sacrifice 10", "moveinit 15"] {
+        assert!(game.legal_moves().iter().all(|&i| MOVES[i].kind() != MoveKind::Select));
+        assert!(game.process_move(m.parse().unwrap()), "{}", m);
+    }
+    assert!(!game.process_move(Move::Select(Size::Small, Color::Red)));
+
+    // Skipping the verbs a phase rules out never hides a playable move
+    for seed in 0..50 {
+        let game = crate::fuzz::apply_random_game(seed, 4 + seed as usize % 16);
+        let every: Vec<usize> = (0..MOVE_COUNT)
+            .filter(|&i| game.clone().process_move_idx(i))
+            .collect();
+        assert_eq!(game.legal_moves(), every);
+    }
+}
+
+#[test]
+fn test_attack() {
+    // No red anywhere: White's homeworld is green and blue, its ship large yellow
+    let base = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    let attack = |game: &mut Game, key: u8| game.process_move(Move::Attack(Key(key)));
+
+    // Attacking needs red from a star or one of the attacker's ships in the system
+    let mut game = base.clone();
+    place_ship(&mut game, 18, 24, Player::Black);
+    assert!(!attack(&mut game.clone(), 18));
+    place_ship(&mut game, 0, 24, Player::White);
+    assert!(attack(&mut game, 18));
+
+    // The target changes hands but stays put, and the turn passes
+    let ship = PieceInfo::Ship {
+        size: Size::Small,
+        color: Color::Green,
+        owner: Player::White,
+        system: SystemId(Key(24)),
+    };
+    assert_eq!(game.piece_at(18), Some(ship));
+    assert_eq!(game.to_move(), Player::Black);
+    assert_eq!(game.check_invariants(), Ok(()));
+
+    // A red sacrifice lends red access for its attacks
+    let mut game = base.clone();
+    place_ship(&mut game, 18, 24, Player::Black);
+    place_ship(&mut game, 0, 12, Player::White);
+    assert!(game.process_move("sacrifice 0".parse().unwrap()));
+    assert!(attack(&mut game, 18));
+
+    // The attacker needs a ship at least as large as the target
+    let mut game = base.clone();
+    place_ship(&mut game, 0, 12, Player::White);
+    place_ship(&mut game, 33, 12, Player::Black);
+    assert!(!attack(&mut game.clone(), 33));
+    assert!(attack(&mut game, 9));
+
+    // Even mid-sacrifice, with no ship of its own in the system
+    let mut game = base.clone();
+    place_ship(&mut game, 0, 24, Player::White);
+    assert!(game.process_move("sacrifice 0".parse().unwrap()));
+    assert!(!attack(&mut game, 9));
+
+    // Of two identical targets, only the lower key may be named
+    let mut game = base.clone();
+    place_ship(&mut game, 18, 24, Player::Black);
+    place_ship(&mut game, 19, 24, Player::Black);
+    place_ship(&mut game, 0, 24, Player::White);
+    assert!(!attack(&mut game.clone(), 19));
+    assert!(attack(&mut game, 18));
+}
+
+#[test]
+fn test_winning_moves() {
+    let mut game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    // White builds two small yellows at home and sends the large one to Black's homeworld
+    for m in [
+        "construct 15",
+        "pass",
+        "construct 10",
+        "pass",
+        "moveinit 15",
+        "movefinish 12",
+    ] {
+        assert!(game.process_move(m.parse().unwrap()), "{}", m);
+    }
+    assert!(game.winning_moves().is_empty());
+    assert!(game.process_move(Move::Pass));
+
+    // A fourth yellow in Black's homeworld collapses its yellow star and every ship there.
+    // Sacrificing a small yellow for the same move would leave White's homeworld empty too.
+    let wins = game.winning_moves();
+    let init = MOVES.iter().position(|&m| m == Move::MoveInit(Key(10))).unwrap();
+    assert_eq!(wins, vec![init]);
+    assert!(game.process_move(Move::MoveInit(Key(10))));
+    assert!(game.process_move(Move::MoveFinish(Key(12))));
+    assert_eq!(game.game_result(), GameResult::Win(Player::White));
+}
+
+#[test]
+fn test_catastrophe_in_flight() {
+    let mut game = Game::from_system_list(
+        "white: g3 b1 | g1 g1 g2 y1 |\nblack: r2 y2 | | r3\nturn: white",
+    )
+    .unwrap();
+    game.set_catastrophe_mode(CatastropheMode::Manual);
+    let green = Move::Catastrophe(make_key(Color::Green, Size::Small, 0).unwrap());
+    assert!(game.clone().process_move(green));
+
+    // A ship in flight could be swept away before it arrives, so no catastrophe till then
+    let yellow = make_key(Color::Yellow, Size::Small, 0).unwrap();
+    assert!(game.process_move(Move::MoveInit(yellow)));
+    assert!(!game.process_move(green));
+    assert!(game.process_move(Move::MoveFinish(make_key(Color::Red, Size::Medium, 0).unwrap())));
+    assert_eq!(game.check_invariants(), Ok(()));
+}
+
+#[test]
+fn test_winning_sacrifice_plans() {
+    let game = Game::from_system_list("white: r2 b1 | g2 |\nblack: y2 b2 | r3 r1 | g1\nturn: white")
+        .unwrap();
+    let index = |m: &str| MOVES.iter().position(|&v| v == m.parse().unwrap()).unwrap();
+
+    // White can take Black's last ship with the turn's action, or with an action from
+    // sacrificing either red ship. The large one leaves two more actions to spend.
+    let wins = game.winning_sacrifice_plans();
+    assert!(wins.contains(&vec![index("sacrifice 0"), index("attack 18")]));
+    assert!(wins.contains(&vec![index("sacrifice 6"), index("attack 18"), index("pass")]));
+    assert!(wins.iter().all(|plan| plan[1] == index("attack 18")));
+    assert!(wins.len() < game.sacrifice_plans().len());
+    let mut moves = game.winning_moves();
+    moves.sort();
+    let mut expected = vec![index("attack 18"), index("sacrifice 0"), index("sacrifice 6")];
+    expected.sort();
+    assert_eq!(moves, expected);
+}
+
+#[test]
+fn test_search_memo() {
+    // Position 0 wins through 2 and position 1 only through 0, so reaching 1 from inside
+    // the search of 0 mustn't settle 1 as a loss
+    fn wins(position: u64, memo: &mut SearchMemo) -> bool {
+        let next: &[u64] = match position {
+            0 => &[1, 2],
+            1 => &[0],
+            _ => return true,
+        };
+        memo.visit(position, false, |memo| next.iter().any(|&p| wins(p, memo)))
+    }
+    let mut memo = SearchMemo::default();
+    assert!(wins(0, &mut memo));
+    assert!(wins(1, &mut memo));
+    assert!(memo.open.is_empty());
+    assert_eq!(memo.cut, None);
+}
+
+#[test]
+fn test_mutual_destruction() {
+    for rule in [MutualDestruction::Draw, MutualDestruction::MoverLoses] {
+        let mut game = setup_game(
+            ["select large green", "select small blue", "select large yellow"],
+            ["select medium yellow", "select medium green", "select small yellow"],
+        );
+        game.set_mutual_destruction(rule);
+        // White sacrifices one small yellow to fly the other out of its homeworld, leaving
+        // no White ship at home and four yellows in Black's
+        for m in [
+            "construct 15",
+            "pass",
+            "construct 10",
+            "pass",
+            "moveinit 15",
+            "movefinish 12",
+            "pass",
+            "sacrifice 10",
+            "moveinit 11",
+            "movefinish 12",
+        ] {
+            assert!(game.process_move(m.parse().unwrap()), "{}", m);
+        }
+        assert!(game.homeworld_lost(Player::White));
+        assert!(game.homeworld_lost(Player::Black));
+        let expected = match rule {
+            MutualDestruction::Draw => GameResult::Draw,
+            MutualDestruction::MoverLoses => GameResult::Win(Player::Black),
+        };
+        assert_eq!(game.game_result(), expected);
+        assert!(!game.process_move(Move::Pass));
+    }
+}
+
+#[test]
+fn test_is_stuck() {
+    // White's homeworld and lone ship are all red, with nothing at home to attack
+    let game = setup_game(
+        ["select large red", "select small red", "select medium red"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    assert!(game.is_stuck(Player::White));
+    assert!(!game.is_stuck(Player::Black));
+
+    // A Black ship arriving in White's homeworld gives the red ship a target
+    let mut game = game;
+    assert!(game.process_move(Move::Pass));
+    assert!(game.process_move("moveinit 9".parse().unwrap()));
+    assert!(game.process_move("movefinish 6".parse().unwrap()));
+    assert!(!game.is_stuck(Player::White));
+
+    // Nobody is stuck while homeworlds are still being chosen
+    assert!(!Game::new().is_stuck(Player::White));
+}
+
+#[test]
+fn test_legal_moves_excluding_suicidal() {
+    // Both homeworlds are overpopulated: four yellows in White's, four greens in Black's
+    let mut game = Game::from_system_list(
+        "white: y3 b1 | y1 y2 y2 |
+         black: g2 r1 | | g1 g3 g3
+         turn: white",
+    )
+    .unwrap();
+    game.set_catastrophe_mode(CatastropheMode::Manual);
+    let own = Move::Catastrophe(Key(9)).index().unwrap();
+    let theirs = Move::Catastrophe(Key(18)).index().unwrap();
+
+    // Both are legal, but only collapsing Black's homeworld leaves White standing
+    let all = game.legal_moves();
+    assert!(all.contains(&own) && all.contains(&theirs));
+    let safe = game.legal_moves_excluding_suicidal();
+    let dropped: Vec<usize> = all.into_iter().filter(|i| !safe.contains(i)).collect();
+    assert_eq!(dropped, vec![own]);
+    assert!(safe.contains(&theirs));
+}
+
+#[test]
+fn test_setup_order() {
+    let mut game = Game::new_with(SetupOrder::BlackFirst);
+    assert_eq!(game.to_move(), Player::Black);
+    for m in [
+        "select medium yellow",
+        "select medium green",
+        "select small yellow",
+        "select large green",
+        "select small blue",
+        "select large yellow",
+    ] {
+        assert!(game.process_move(m.parse().unwrap()), "{}", m);
+    }
+
+    // Black chose first, so Black's pieces took the lower copies
+    assert!(game.bstar == KeyMaybe::some(Key(12)));
+    assert!(game.wstar == KeyMaybe::some(Key(24)));
+    let ship = |key: u8, owner: Player, star: u8| PieceInfo::Ship {
+        size: Key(key).size(),
+        color: Key(key).color(),
+        owner,
+        system: SystemId(Key(star)),
+    };
+    assert_eq!(game.piece_at(9), Some(ship(9, Player::Black, 12)));
+    assert_eq!(game.piece_at(15), Some(ship(15, Player::White, 24)));
+
+    // And Black takes the first turn of play
+    assert_eq!(game.to_move(), Player::Black);
+    assert!(game.setup_options().is_empty());
+    assert!(game.process_move(Move::Pass));
+    assert_eq!(game.to_move(), Player::White);
+}
+
+#[test]
+fn test_bank_count() {
+    // Every copy of every piece is either in the bank or found by walking the systems
+    let census = |game: &Game| {
+        let mut in_play = [0; PIECE_COUNT / COPY_COUNT];
+        for system in game.systems() {
+            let (star, child, sibling) = match game.board[system.0] {
+                Piece::Star { child } => (system.0, KeyMaybe::some(child), KeyMaybe::none()),
+                Piece::BinaryFirst { child, sibling } => (system.0, child, sibling),
+                _ => unreachable!(),
+            };
+            let ships = child.get().into_iter().flat_map(|ckey| game.board.sibling_iter(ckey));
+            for key in [star].into_iter().chain(sibling.get()).chain(ships.map(|(_, k)| k)) {
+                in_play[key.0 as usize / COPY_COUNT] += 1;
+            }
+        }
+        for color in Color::list() {
+            for size in Size::list() {
+                let slot = color as usize * SIZE_COUNT + size as usize;
+                assert_eq!(game.bank_count(size, color) + in_play[slot], COPY_COUNT);
+            }
+        }
+    };
+    census(&Game::new());
+
+    // Random games build, capture, sacrifice and collapse along the way
+    for seed in 0..300 {
+        census(&crate::fuzz::apply_random_game(seed, (seed % 150) as usize));
+    }
+}
+
+#[test]
+fn test_best_sacrifice() {
+    // White's small and medium reds can each only capture the lone small intruder,
+    // so the cheaper sacrifice wins the tie
+    let game = Game::from_system_list(
+        "white: g3 b1 | r1 r2 | y1
+         black: y2 | | g2
+         turn: white",
+    )
+    .unwrap();
+    assert_eq!(game.best_sacrifice(), Some((Key(0), 1)));
+
+    // With a second intruder the medium red can spend both of its attacks
+    let game = Game::from_system_list(
+        "white: g3 b1 | r1 r2 | y1 y1
+         black: y2 | | g2
+         turn: white",
+    )
+    .unwrap();
+    assert_eq!(game.best_sacrifice(), Some((Key(3), 2)));
+
+    // Nor is another sacrifice legal while one is under way
+    let mut game = game;
+    assert!(game.process_move(Move::Sacrifice(Key(0))));
+    assert_eq!(game.best_sacrifice(), None);
+}
+
+#[test]
+fn test_full_spectrum() {
+    // Green from White's home star, yellow from its ship there, red and blue from a colony
+    let game = Game::from_system_list(
+        "white: g3 b1 | y3 |
+         black: y2 | | g2 b3
+         scout: r2 | b1 |
+         turn: white",
+    )
+    .unwrap();
+    let all = vec![Ability::Attack, Ability::Move, Ability::Construct, Ability::Transform];
+    assert_eq!(game.available_abilities(Player::White), all);
+    assert!(game.has_full_spectrum(Player::White));
+    // Black's blue ship is at home, but Black has no red anywhere
+    assert!(!game.has_full_spectrum(Player::Black));
+
+    // Without the blue ship White's only blue is its home's second star, which grants nothing
+    let game = Game::from_system_list(
+        "white: g3 b1 | y3 |
+         black: y2 | | g2 b3
+         scout: r2 | g1 |
+         turn: white",
+    )
+    .unwrap();
+    assert_eq!(
+        game.available_abilities(Player::White),
+        vec![Ability::Attack, Ability::Move, Ability::Construct]
+    );
+    assert!(!game.has_full_spectrum(Player::White));
+}
+
+#[test]
+fn test_has_legal_action() {
+    // Positions from random games at every stage, mid-sacrifice and mid-movement included
+    let pass = Move::Pass.index().unwrap();
+    for seed in 0..40 {
+        for length in [0, 3, 6, 7, 10, 15, 25, 40, 80] {
+            let game = apply_random_game(seed, length);
+            let has_action = game.has_legal_action();
+            let must_pass = game.must_pass();
+            let moves = game.legal_moves();
+            let at = format!("seed {} length {}", seed, length);
+            assert_eq!(has_action, moves.iter().any(|&i| i != pass), "{}", at);
+            assert_eq!(must_pass, moves == [pass], "{}", at);
+
+            // The cached list gives the same answers
+            assert_eq!(game.has_legal_action(), has_action);
+            assert_eq!(game.must_pass(), must_pass);
+        }
+    }
+}
+
+#[test]
+fn test_successors() {
+    let game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    let successors = game.successors();
+    let moves: Vec<Move> = game.legal_moves().into_iter().map(|i| MOVES[i]).collect();
+    assert_eq!(successors.iter().map(|&(m, _)| m).collect::<Vec<_>>(), moves);
+    for (m, next) in &successors {
+        assert_eq!(next.hash(), game.with_move(*m).unwrap().hash());
+        assert_eq!(next.ply(), game.ply() + 1);
+    }
+
+    // A sacrifice stops with its action still to spend, and White still to move
+    let (_, after) = successors
+        .iter()
+        .find(|(m, _)| *m == "sacrifice 15".parse().unwrap())
+        .unwrap();
+    assert_eq!(after.pending_actions(), Some((Ability::Move, 3)));
+    assert_eq!(after.to_move(), Player::White);
+    assert!(Game::new().successors().iter().all(|(m, _)| m.kind() == MoveKind::Select));
+}
+
+#[test]
+fn test_process_move_idx_out_of_range() {
+    let mut game = Game::new();
+    assert!(!game.process_move_idx(MOVE_COUNT));
+    assert!(!game.process_move_idx(usize::MAX));
+    assert_eq!(game.ply(), 0);
+    assert_eq!(game.hash(), Game::new().hash());
+    assert!(game.process_move_idx(game.legal_moves()[0]));
+}
+
+#[test]
+fn test_color_economy() {
+    // White is long on yellow and has no blue; Black is the reverse
+    let game = Game::from_system_list(
+        "white: y3 g1 | y2 y1 g3 |\nblack: b2 r1 | | b3 g2\nturn: white",
+    )
+    .unwrap();
+    assert_eq!(game.color_economy(Player::White), [0, 3, 0, -1]);
+    assert_eq!(game.color_economy(Player::Black), [0, -2, 0, 2]);
+
+    // Each side is strong where the other is weak
+    let white = game.color_economy(Player::White);
+    assert!(white[Color::Yellow as usize] > 0 && white[Color::Blue as usize] < 0);
+}
+
+#[test]
+fn test_reachable_colors() {
+    assert_eq!(Game::new().reachable_colors(Player::White), [true; COLOR_COUNT]);
+
+    // White has neither green nor blue anywhere, and no red ship
+    let game = Game::from_system_list(
+        "white: r2 y1 | y3 |\nblack: b2 g1 | | g3\nturn: white",
+    )
+    .unwrap();
+    assert_eq!(game.reachable_colors(Player::White), [false, true, false, false]);
+    assert_eq!(game.reachable_colors(Player::Black), [true; COLOR_COUNT]);
+
+    // A blue star to sit under opens every color again
+    let game = Game::from_system_list(
+        "white: r2 y1 | y3 |\nblack: b2 g1 | | g3\nc: b1 | y2 |\nturn: white",
+    )
+    .unwrap();
+    assert_eq!(game.reachable_colors(Player::White), [true; COLOR_COUNT]);
+}
+
+#[test]
+fn test_best_material_gain() {
+    // White can take Black's small green at home, or collapse three of Black's reds
+    let position = "white: r2 b1 | y3 | g1\nblack: b2 y1 | | g3 y2\n\
+                    mid: r1 | | r3 r3 r2\nturn: white";
+    let mut game = Game::from_system_list(position).unwrap();
+    game.set_catastrophe_mode(CatastropheMode::Manual);
+    let attack = MOVES.iter().position(|&m| m == Move::Attack(Key(18))).unwrap();
+    let mut captured = game.clone();
+    assert!(captured.process_move_idx(attack));
+    let balance = game.material_balance(Player::White);
+    assert_eq!(captured.material_balance(Player::White) - balance, 2);
+
+    let (best, gain) = game.best_material_gain();
+    assert!(matches!(MOVES[best.unwrap()], Move::Catastrophe(key) if key.color() == Color::Red));
+    assert_eq!(gain, 8);
+
+    // Nothing to gain once the game is over
+    let mut won = Game::from_system_list("white: g3 b1 | y3 |\nblack: r2 b1 | r3 | g1\nturn: white")
+        .unwrap();
+    assert!(won.process_move(Move::Attack(Key(18))));
+    assert_eq!(won.best_material_gain(), (None, 0));
+}
+
+#[test]
+fn test_thread_safety() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Game>();
+    assert_send_sync::<Move>();
+    assert_send_sync::<Unmake>();
+    assert_send_sync::<SetupError>();
+    assert_send_sync::<BoardDiff>();
+
+    // Threads reading one position race to fill its move cache and all agree
+    let game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    let expected = game.clone().legal_moves();
+    std::thread::scope(|scope| {
+        let readers: Vec<_> = (0..4).map(|_| scope.spawn(|| game.legal_moves())).collect();
+        for reader in readers {
+            assert_eq!(reader.join().unwrap(), expected);
+        }
+    });
+    assert_eq!(game.legal_moves(), expected);
+}
+
+#[test]
+fn test_complexity() {
+    // A ship each, and nothing to fight over
+    let quiet = Game::from_system_list(
+        "white: g3 b1 | y1 |\nblack: b2 r1 | | y1\nturn: white",
+    )
+    .unwrap();
+    // Two of Black's ships in reach of White's reds, and red overpopulated in the middle
+    let crowded = Game::from_system_list(
+        "white: r2 b1 | y3 g2 | g1 y1\nblack: b2 y1 | | g3 y2\n\
+         mid: r1 | g1 | r3 r2 r2\nturn: white",
+    )
+    .unwrap();
+    assert_eq!(quiet.legal_moves_of_kind(MoveKind::Attack).len(), 0);
+    assert_eq!(crowded.legal_moves_of_kind(MoveKind::Attack).len(), 2);
+    assert_eq!(crowded.legal_moves_of_kind(MoveKind::Catastrophe).len(), 1);
+
+    // Construct, sacrifice, move and pass from the y1, and 4 for each of two systems
+    assert_eq!(quiet.legal_moves().len(), 4);
+    assert_eq!(quiet.complexity(), 4 + 4 * 2);
+    // Two attacks, the catastrophe, three constructs, three sacrifices, two moves and pass,
+    // with 4 for each of three systems and 8 for each tactic
+    assert_eq!(crowded.legal_moves().len(), 12);
+    assert_eq!(crowded.complexity(), 12 + 4 * 3 + 8 * 3);
+    assert!(quiet.complexity() < crowded.complexity());
+}
+
+#[test]
+fn test_overpopulation_count() {
+    let red_in_bank = |game: &Game| {
+        Size::list().iter().map(|&size| game.bank_count(size, Color::Red)).sum::<usize>()
+    };
+    // Binary stars only occur at homeworlds, so those cases use Black's
+    let home = "white: g3 b2 | y3 |\nblack: g2 b1 | | y2";
+    for (position, collapses) in [
+        (format!("{home}\nmid: b1 | r1 r1 r2 | r3"), true), // Four ships
+        (format!("{home}\nmid: r1 | r2 r2 | r3"), true),    // Three ships and the star
+        (format!("{home}\nmid: b1 | r2 r2 | r3"), false),   // Three ships, another color's star
+        ("white: g3 b2 | y3 |\nblack: r1 r2 | r3 | r3 y2".to_string(), true), // Both stars
+        ("white: g3 b2 | y3 |\nblack: b1 r2 | r3 | r3 r1".to_string(), true), // Second star
+        ("white: g3 b2 | y3 |\nblack: r1 r2 | | r3 y2".to_string(), false),   // One ship short
+    ] {
+        let position = format!("{position}\nturn: white");
+        let mut game = Game::from_system_list(&position).expect(&position);
+        game.set_catastrophe_mode(CatastropheMode::Manual);
+        let named = game.overpopulations();
+        assert_eq!(named.len(), collapses as usize, "{}", position);
+
+        // Every red piece in the system goes back to the bank
+        if let Some(&key) = named.first() {
+            assert_eq!(key.color(), Color::Red);
+            assert!(game.process_move(Move::Catastrophe(key)));
+            assert_eq!(red_in_bank(&game), 9, "{}", position);
+        }
+    }
+}
+
+#[test]
+fn test_bank_delta() {
+    let mut game = Game::new();
+    let (red, yellow, green) = (Color::Red as usize, Color::Yellow as usize, Color::Green as usize);
+    let (small, large) = (Size::Small as usize, Size::Large as usize);
+
+    // A selection takes its star from the bank
+    let mut expected = [[0; SIZE_COUNT]; COLOR_COUNT];
+    expected[green][large] = -1;
+    assert_eq!(game.bank_delta("select large green".parse().unwrap()), Some(expected));
+    assert_eq!(game.bank_delta(Move::Pass), None);
+
+    for m in [
+        "select large green",
+        "select small blue",
+        "select large yellow",
+        "select medium yellow",
+        "select medium green",
+        "select small yellow",
+    ] {
+        assert!(game.process_move(m.parse().unwrap()), "{}", m);
+    }
+    // A construction takes the smallest of its color; a sacrifice returns the ship
+    let mut expected = [[0; SIZE_COUNT]; COLOR_COUNT];
+    expected[yellow][small] = -1;
+    assert_eq!(game.bank_delta("construct 15".parse().unwrap()), Some(expected));
+    let mut expected = [[0; SIZE_COUNT]; COLOR_COUNT];
+    expected[yellow][large] = 1;
+    assert_eq!(game.bank_delta("sacrifice 15".parse().unwrap()), Some(expected));
+    assert_eq!(game.ply(), 6); // Previewing leaves the game alone
+
+    // A transform trades one piece for another
+    let game = Game::from_system_list(
+        "white: b3 g1 | y3 |\nblack: b2 y1 | | g2\nturn: white",
+    )
+    .unwrap();
+    let mut expected = [[0; SIZE_COUNT]; COLOR_COUNT];
+    expected[yellow][large] = 1;
+    expected[red][large] = -1;
+    assert_eq!(game.bank_delta(Move::Transform(Key(15), Color::Red)), Some(expected));
+}
+
+#[test]
+fn test_setup_progress() {
+    let mut game = Game::new();
+    assert_eq!(game.setup_progress(), SetupProgress::default());
+
+    let steps = [
+        ("select large green", (Size::Large, Color::Green)),
+        ("select small blue", (Size::Small, Color::Blue)),
+        ("select large yellow", (Size::Large, Color::Yellow)),
+        ("select medium yellow", (Size::Medium, Color::Yellow)),
+        ("select medium green", (Size::Medium, Color::Green)),
+        ("select small yellow", (Size::Small, Color::Yellow)),
+    ];
+    let mut expected = SetupProgress::default();
+    for (i, (m, piece)) in steps.into_iter().enumerate() {
+        assert!(game.process_move(m.parse().unwrap()), "{}", m);
+        let side = if i < 3 { &mut expected.white } else { &mut expected.black };
+        match i % 3 {
+            0 => side.star1 = Some(piece),
+            1 => side.star2 = Some(piece),
+            _ => side.ship = Some(piece),
+        }
+        assert_eq!(game.setup_progress(), expected, "{}", m);
+    }
+
+    // A ship built during play doesn't change what's reported
+    assert!(game.process_move("construct 15".parse().unwrap()));
+    assert_eq!(game.setup_progress(), expected);
+}
+
+#[test]
+fn test_from_moves_with_setup() {
+    for setup in [StandardSetup::SmallLarge, StandardSetup::SmallMedium] {
+        let game = Game::from_moves_with_setup(setup, &[]).unwrap();
+        assert_eq!(game.ply(), 6);
+        assert_eq!(game.setup_progress(), setup.progress());
+        assert_eq!(game.game_phase(), GamePhase::Opening);
+        assert_eq!(game.to_move(), Player::White);
+    }
+
+    let game = Game::from_moves_with_setup(StandardSetup::SmallLarge, &[]).unwrap();
+    assert_eq!(
+        game.to_system_list(),
+        Game::from_system_list("white: y1 b3 | g3 |\nblack: r1 b3 | | g3\nturn: white")
+            .unwrap()
+            .to_system_list()
+    );
+
+    // Moves count from the first one after setup
+    let moves: Vec<Move> = ["construct 24", "pass", "pass"]
+        .iter()
+        .map(|m| m.parse().unwrap())
+        .collect();
+    let game = Game::from_moves_with_setup(StandardSetup::SmallLarge, &moves[..2]).unwrap();
+    assert_eq!(game.ply(), 8);
+    assert_eq!(
+        Game::from_moves_with_setup(StandardSetup::SmallLarge, &moves).map(|g| g.ply()),
+        Ok(9)
+    );
+    let refused = [Move::Pass, Move::Select(Size::Small, Color::Red)];
+    assert!(matches!(
+        Game::from_moves_with_setup(StandardSetup::SmallMedium, &refused),
+        Err((1, _))
+    ));
+}
+
+#[test]
+fn test_process_move_events() {
+    // Two overpopulated colonies; White takes a yellow in the first, and both collapse
+    // as the turn ends, in key order of the ships naming them
+    let mut game = Game::from_system_list(
+        "white: g3 b1 | r3 y1 |\nblack: y3 b2 | | g2\na: y3 | r2 | y2 y2 y1\n\
+         b: g1 | b1 | g1 g1 g2\nturn: white",
+    )
+    .unwrap();
+    let refused = Move::Attack(make_key(Color::Green, Size::Medium, 0).unwrap());
+    assert_eq!(game.process_move_events(refused), None);
+    let target = make_key(Color::Yellow, Size::Small, 1).unwrap();
+    let events = game.process_move_events(Move::Attack(target)).unwrap();
+    assert_eq!(
+        events,
+        vec![
+            GameEvent::MovePlayed {
+                player: Player::White,
+                played: Move::Attack(target),
+            },
+            GameEvent::PieceCaptured {
+                key: target.0,
+                by: Player::White,
+            },
+            GameEvent::Catastrophe {
+                system: SystemId(Key(16)),
+                color: Color::Yellow,
+                removed: vec![3, 10, 12, 13, 16],
+            },
+            GameEvent::Catastrophe {
+                system: SystemId(Key(18)),
+                color: Color::Green,
+                removed: vec![18, 19, 20, 22, 28],
+            },
+            GameEvent::PhaseChanged {
+                phase: GamePhase::Opening,
+            },
+        ]
+    );
+    assert_eq!(
+        events[2].to_json(),
+        r#"{"type":"catastrophe","system":16,"color":"yellow","removed":[3,10,12,13,16]}"#
+    );
+
+    // A named catastrophe that takes White's only star ends the game
+    let mut game = Game::from_system_list(
+        "white: r3 | g1 | r1 r2 r2
+         black: y2 g1 | | b3
+         turn: black",
+    )
+    .unwrap();
+    let key = game.legal_catastrophes()[0];
+    let events = game.process_move_events(Move::Catastrophe(key)).unwrap();
+    assert_eq!(events.len(), 4);
+    assert!(matches!(events[1], GameEvent::Catastrophe { color: Color::Red, .. }));
+    assert_eq!(events[3], GameEvent::GameOver(GameResult::Win(Player::Black)));
+    assert_eq!(
+        events[3].to_json(),
+        r#"{"type":"game_over","result":"win","winner":"black"}"#
+    );
+    assert_eq!(
+        events[0].to_json(),
+        format!(r#"{{"type":"move_played","player":"black","move":"catastrophe {}"}}"#, key.0)
+    );
+    assert_eq!(
+        GameEvent::PhaseChanged { phase: GamePhase::Opening }.to_json(),
+        r#"{"type":"phase_changed","phase":"opening"}"#
+    );
+    assert_eq!(
+        GameEvent::GameOver(GameResult::Draw).to_json(),
+        r#"{"type":"game_over","result":"draw"}"#
+    );
+}
+
+#[test]
+fn test_dedupe_moves() {
+    // Two identical small yellows at White's home
+    let game = Game::from_system_list(
+        "white: g3 b1 | y1 y1 g3 |\nblack: y2 b2 | | g2\nturn: white",
+    )
+    .unwrap();
+    let first = make_key(Color::Yellow, Size::Small, 0).unwrap();
+    let second = make_key(Color::Yellow, Size::Small, 1).unwrap();
+    let moves: Vec<usize> = [
+        Move::Sacrifice(first),
+        Move::Sacrifice(second),
+        Move::Construct(first),
+        Move::Construct(second),
+        Move::Sacrifice(first),
+    ]
+    .iter()
+    .map(|m| m.index().unwrap())
+    .collect();
+    assert_eq!(game.dedupe_moves(&moves), vec![moves[0], moves[2]]);
+
+    // Legal moves already name one ship per kind, so nothing drops out of them
+    for seed in 0..20 {
+        let game = apply_random_game(seed, 30);
+        let legal = game.legal_moves();
+        assert_eq!(game.dedupe_moves(&legal), legal);
+    }
+}
+
+#[test]
+fn test_catastrophe_vulnerabilities() {
+    // White's three greens crowd their home; Black's yellow home star and two yellow
+    // ships are as exposed, and Black's two small greens sit under a green colony star
+    let game = Game::from_system_list(
+        "white: r3 b1 | g3 g2 g1 |\nblack: y2 b2 | | g3 y3 y1\nc: g2 | y1 | g1 g1\n\
+         turn: white",
+    )
+    .unwrap();
+    let white = game.homeworld(Player::White).unwrap();
+    let black = game.homeworld(Player::Black).unwrap();
+    let colony = *game.systems().iter().find(|&&s| s != white && s != black).unwrap();
+    assert_eq!(game.catastrophe_vulnerabilities(Player::White), vec![(white, Color::Green, 3)]);
+    assert_eq!(
+        game.catastrophe_vulnerabilities(Player::Black),
+        vec![(black, Color::Yellow, 3), (colony, Color::Green, 2)]
+    );
+
+    // One more green at White's home and it's an overpopulation instead
+    let game = Game::from_system_list(
+        "white: r3 b1 | g3 g2 g1 g1 |\nblack: y2 b2 | | g3\nturn: white",
+    )
+    .unwrap();
+    assert!(game.catastrophe_vulnerabilities(Player::White).is_empty());
+    assert!(!game.overpopulations().is_empty());
+}
+
+#[test]
+fn test_defensive_moves() {
+    // Black's reds at White's home need one more to overpopulate it, and Black's green
+    // ship there can build it. White's small yellow can't take either red.
+    let game = Game::from_system_list(
+        "white: r1 b2 | y1 | r3 r2 g1\nblack: y3 b1 | | b3\nturn: white",
+    )
+    .unwrap();
+    let threat = Move::Construct(make_key(Color::Red, Size::Medium, 0).unwrap());
+    let mut quiet = game.clone();
+    assert!(quiet.process_move(Move::Pass));
+    let collapsed = quiet.with_move(threat).unwrap();
+    assert_ne!(collapsed.homeworld(Player::White), game.homeworld(Player::White));
+
+    // Taking the builder is the only defense; flying the yellow off abandons home
+    let green = make_key(Color::Green, Size::Small, 0).unwrap();
+    assert_eq!(game.defensive_moves(threat), vec![Move::Attack(green).index().unwrap()]);
+    assert!(game.legal_moves().len() > 1);
+}
+
+#[test]
+fn test_opponent_best_response_loses_me() {
+    // Black's large yellow can fly into White's home from the colony
+    let game = Game::from_system_list(
+        "white: g3 b1 | y2 y1 |\nblack: b2 r1 | | g2\nc: r2 | | y3\nturn: white",
+    )
+    .unwrap();
+    let construct = Move::Construct(make_key(Color::Yellow, Size::Small, 0).unwrap());
+    assert!(game.with_move(construct).is_some());
+
+    // A third yellow at home lets Black bring the fourth and sweep White's fleet away
+    assert!(game.opponent_best_response_loses_me(construct));
+    let mut careless = game.with_move(construct).unwrap();
+    while careless.to_move() == Player::Black && !careless.is_terminal() {
+        assert!(careless.process_move_idx(careless.winning_moves()[0]));
+    }
+    assert_eq!(careless.game_result(), GameResult::Win(Player::Black));
+
+    // Two yellows are safe, and a refused move warns of nothing
+    assert!(!game.opponent_best_response_loses_me(Move::Pass));
+    assert!(!game.opponent_best_response_loses_me(Move::Attack(Key(0))));
+
+    // A sacrifice is judged over the whole turn it opens: White can spend both moves
+    // without ever leaving three yellows for Black to top up
+    let sacrifice = Move::Sacrifice(make_key(Color::Yellow, Size::Medium, 0).unwrap());
+    assert!(!game.opponent_best_response_loses_me(sacrifice));
+}
+
+#[test]
+fn test_star_size_map() {
+    let game = Game::from_system_list(
+        "white: g3 b1 | y3 |\nblack: b2 y2 | | g2\nc: r2 | g1 |\nturn: white",
+    )
+    .unwrap();
+    let map = game.star_size_map();
+    let sizes: Vec<_> = map.iter().map(|&(_, sizes)| sizes).collect();
+    assert_eq!(map.iter().map(|&(system, _)| system).collect::<Vec<_>>(), game.systems());
+    // In key order: the red colony, White's green-first binary, Black's blue-first one
+    assert_eq!(
+        sizes,
+        [
+            (Size::Medium, Size::Medium),
+            (Size::Large, Size::Small),
+            (Size::Medium, Size::Medium)
+        ]
+    );
+
+    // Only White's homeworld shares no size with the others
+    let white = map[1].0;
+    assert_eq!(game.reachability(), [(map[0].0, white), (white, map[2].0)]);
+}
+
+#[test]
+fn test_construct_smallest_available() {
+    let red_construct = |game: &Game| {
+        let moves = game.legal_moves_of_kind(MoveKind::Construct);
+        moves
+            .into_iter()
+            .find(|&i| matches!(MOVES[i], Move::Construct(key) if key.color() == Color::Red))
+    };
+
+    // Every small and medium red is in play, so White's red builds a large one
+    let game = Game::from_system_list(
+        "white: g3 b1 | r1 r2 |\nblack: b2 y1 | | g2 r1 r2\nc1: r1 | r2 |\nturn: white",
+    )
+    .unwrap();
+    let i = red_construct(&game).unwrap();
+    let mut expected = [[0; SIZE_COUNT]; COLOR_COUNT];
+    expected[Color::Red as usize][Size::Large as usize] = -1;
+    assert_eq!(game.bank_delta(MOVES[i]), Some(expected));
+
+    // With the larges gone too, there's nothing left to build
+    let game = Game::from_system_list(
+        "white: g3 b1 | r1 r2 |\nblack: b2 y1 | | g2 r1 r2 r3\nc1: r1 | r2 |\n\
+         c2: y3 | r3 r3 |\nturn: white",
+    )
+    .unwrap();
+    assert_eq!(red_construct(&game), None);
+    assert_eq!(game.smallest_in_bank(Color::Red), None);
+    assert_eq!(game.smallest_in_bank(Color::Green), make_key(Color::Green, Size::Small, 0));
+}
+
+#[test]
+fn test_ruleset() {
+    assert_eq!(Game::new().ruleset(), RuleSet::default());
+    let ruleset = RuleSet {
+        setup_order: SetupOrder::BlackFirst,
+        catastrophe_mode: CatastropheMode::Manual,
+        catastrophe_cost: CatastropheCost::Action,
+        mutual_destruction: MutualDestruction::MoverLoses,
+    };
+    let mut game = Game::new_with_ruleset(ruleset);
+    assert_eq!(game.ruleset(), ruleset);
+    assert_eq!(game.to_move(), Player::Black);
+    for m in [
+        "select medium yellow",
+        "select medium green",
+        "select small yellow",
+        "select large green",
+        "select small blue",
+        "select large yellow",
+    ] {
+        assert!(game.process_move(m.parse().unwrap()), "{}", m);
+    }
+
+    // The rules ride along with the position
+    let text = game.to_system_list();
+    assert!(text.ends_with(
+        "rules: setup=black-first catastrophes=manual cost=action mutual=mover-loses\n"
+    ));
+    let loaded = Game::from_system_list(&text).unwrap();
+    assert_eq!(loaded.ruleset(), ruleset);
+    assert_eq!(loaded.to_system_list(), text);
+
+    // Standard games write no rules line, and a partial one keeps the other defaults
+    assert!(!Game::from_system_list("white: g3 b1 | y3 |\nblack: b2 y1 | | g2\nturn: white")
+        .unwrap()
+        .to_system_list()
+        .contains("rules"));
+    let partial = "white: g3 b1 | y3 |\nblack: b2 y1 | | g2\nturn: white\nrules: cost=action";
+    let expected = RuleSet {
+        catastrophe_cost: CatastropheCost::Action,
+        ..RuleSet::default()
+    };
+    assert_eq!(Game::from_system_list(partial).unwrap().ruleset(), expected);
+    let unknown = "white: g3 b1 | y3 |\nblack: b2 y1 | | g2\nturn: white\nrules: cost=gold";
+    assert_eq!(Game::from_system_list(unknown).err(), Some(SystemListError::Syntax { line: 4 }));
+}
+
+#[test]
+fn test_fleet_distribution() {
+    let game = Game::from_system_list(
+        "white: g3 b1 | y3 g1 | r1
+         black: y2 r2 | | g2
+         s1: b3 | r1 |
+         turn: white",
+    )
+    .unwrap();
+    let home = SystemId(Key(24));
+    let colony = SystemId(Key(33));
+    assert_eq!(game.fleet_distribution(Player::White), [(home, 2), (colony, 1)]);
+    assert_eq!(game.fleet_distribution(Player::Black), [(SystemId(Key(12)), 1), (home, 1)]);
+}
+
+#[test]
+fn test_mate_in() {
+    // White's medium red can be sacrificed for the two attacks that clear Black's
+    // homeworld, where White's large green waits
+    let mut game = Game::from_system_list(
+        "white: g3 b1 | r2 y1 |
+         black: y2 | g3 | y1 b1
+         turn: white",
+    )
+    .unwrap();
+    assert_eq!(game.mate_in(2), None);
+    assert_eq!(game.mate_in(4), Some(3));
+
+    assert!(game.process_move("sacrifice 3".parse().unwrap()));
+    assert_eq!(game.mate_in(4), Some(2));
+    let attack = game.legal_moves_of_kind(MoveKind::Attack)[0];
+    assert!(game.process_move_idx(attack));
+    assert_eq!(game.mate_in(4), Some(1));
+
+    // Nothing is forced straight after setup
+    let game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    assert_eq!(game.mate_in(3), None);
+}
+
+#[test]
+fn test_make_unmake() {
+    // Every legal move of every position along a few random games is taken back exactly
+    for seed in 0..8 {
+        let mut game = Game::new();
+        for step in 0..60 {
+            if game.is_terminal() {
+                break;
+            }
+            let moves = game.legal_moves();
+            let (pieces, hash) = (game.board.pieces, game.hash());
+            let (text, ply) = (game.to_system_list(), game.ply());
+            for &i in &moves {
+                let token = game.make(MOVES[i]).unwrap();
+                game.unmake(token);
+                assert!(game.board.pieces == pieces, "seed {} step {}: {}", seed, step, MOVES[i]);
+                assert_eq!(game.hash(), hash);
+                assert_eq!(game.to_system_list(), text);
+                assert_eq!(game.ply(), ply);
+            }
+            assert_eq!(game.legal_moves(), moves);
+            let pick = moves[(wyhash64(seed, step) % moves.len() as u64) as usize];
+            assert!(game.process_move_idx(pick));
+        }
+    }
+
+    // Unmaking in reverse order walks back through a whole line
+    let start = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    let mut game = start.clone();
+    let mut tokens = Vec::new();
+    for m in ["construct 15", "pass", "moveinit 15", "movefinish 12"] {
+        tokens.push(game.make(m.parse().unwrap()).unwrap());
+    }
+    assert!(game.make(Move::Attack(Key(0))).is_none());
+    while let Some(token) = tokens.pop() {
+        game.unmake(token);
+    }
+    assert!(game.board.pieces == start.board.pieces);
+    assert_eq!(game.hash(), start.hash());
+    assert_eq!(game.legal_moves(), start.legal_moves());
+}
+
+#[test]
+fn test_catastrophe_setups() {
+    // Three reds already crowd Black's homeworld, and White's green can build a fourth
+    let mut game = Game::from_system_list(
+        "white: g3 b1 | y3 |
+         black: r2 y1 | r1 r2 g1 | b3
+         turn: white",
+    )
+    .unwrap();
+    let construct = Move::Construct(Key(0)).index().unwrap();
+    let black_home = SystemId(Key(3));
+    assert_eq!(game.catastrophe_setups(), [(construct, black_home, Color::Red)]);
+
+    // The construction ends White's turn, so with manual catastrophes Black would get
+    // to choose whether to trigger it
+    let mut manual = game.clone();
+    manual.set_catastrophe_mode(CatastropheMode::Manual);
+    assert!(manual.catastrophe_setups().is_empty());
+
+    // Automatic catastrophes sweep every red out of Black's homeworld at the turn's end
+    assert!(game.process_move_idx(construct));
+    assert_eq!(game.bank_count(Size::Small, Color::Red), 3);
+    assert_eq!(game.homeworld_health(Player::Black).stars, 1);
+}
+
+#[test]
+fn test_legal_move_cache() {
+    let mut game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    assert!(game.legal.get().is_none());
+    let before = game.legal_moves();
+    assert!(game.legal.get().is_some());
+    assert_eq!(game.legal_moves(), before);
+    let construct = Move::Construct(Key(15)).index().unwrap();
+    assert_eq!(game.legal_moves_of_kind(MoveKind::Construct), [construct]);
+
+    // Copies work their moves out afresh, and a move clears the cache
+    assert!(game.clone().legal.get().is_none());
+    assert!(!game.process_move(Move::Attack(Key(9))));
+    assert!(game.legal.get().is_none());
+    assert!(game.process_move("construct 15".parse().unwrap()));
+    let after = game.legal_moves();
+    assert_ne!(after, before);
+    assert_eq!(after, game.legal_moves_where(|_| true));
+    assert_eq!(game.to_move(), Player::Black);
+    assert!(after.contains(&Move::MoveInit(Key(9)).index().unwrap()));
+}
+
+#[test]
+fn test_new_from_board() {
+    use Color::*;
+    use Player::*;
+    use Size::*;
+    let systems = [
+        SystemSpec {
+            stars: vec![(Medium, Yellow), (Medium, Green)],
+            ships: vec![(Small, Yellow, Black)],
+        },
+        SystemSpec {
+            stars: vec![(Small, Red)],
+            ships: vec![(Small, Green, White), (Medium, Blue, Black)],
+        },
+        SystemSpec {
+            stars: vec![(Large, Green), (Small, Blue)],
+            ships: vec![(Large, Yellow, White)],
+        },
+    ];
+    // Each system is named by its first star, the lowest copy of that piece still free
+    let ids = SystemSpec::ids(&systems);
+    let id = |color: Color, size: Size, copy: u8| SystemId::new(make_key(color, size, copy)?.0);
+    assert_eq!(ids, vec![id(Yellow, Medium, 0), id(Red, Small, 0), id(Green, Large, 0)]);
+    let game = Game::new_from_board(&systems, ids[2].unwrap(), ids[0].unwrap(), Black).unwrap();
+    let text = Game::from_system_list(
+        "white: g3 b1 | y3 |
+         black: y2 g2 | | y1
+         scout: r1 | g1 | b2
+         turn: black",
+    )
+    .unwrap();
+    assert_eq!(game.to_system_list(), text.to_system_list());
+    assert_eq!(game.hash(), text.hash());
+    assert!(game.setup_options().is_empty());
+    assert_eq!(game.to_move(), Black);
+
+    // Homeworlds must be two different systems from the list, named by first star
+    let [yellow, green] = [ids[0].unwrap(), ids[2].unwrap()];
+    let error = |systems: &[SystemSpec], white: SystemId, black: SystemId| {
+        Game::new_from_board(systems, white, black, White).err()
+    };
+    let second_red = id(Red, Small, 1).unwrap();
+    assert_eq!(error(&systems, second_red, yellow), Some(SetupError::Homeworld(White)));
+    let blue = id(Blue, Small, 0).unwrap(); // Green's second star
+    assert_eq!(error(&systems, green, blue), Some(SetupError::Homeworld(Black)));
+    assert_eq!(error(&systems, green, green), Some(SetupError::Homeworld(White)));
+
+    // Colonies have one star and some ships, and the bank holds three of each piece:
+    // three large yellows in the colony leave none for White's homeworld after it
+    let mut bad = systems.clone();
+    bad[1].ships.clear();
+    assert_eq!(error(&bad, green, yellow), Some(SetupError::BadSystem { system: 1 }));
+    let mut bad = systems.clone();
+    bad[1].ships.extend([(Large, Yellow, Black); 3]);
+    assert_eq!(error(&bad, green, yellow), Some(SetupError::OutOfPieces { system: 2 }));
+}
+
+#[test]
+fn test_homeworld() {
+    let mut game = Game::new();
+    assert_eq!(game.homeworld(Player::White), None);
+    for m in ["select large green", "select small blue", "select large yellow"] {
+        assert!(game.process_move(m.parse().unwrap()));
+    }
+    assert_eq!(game.homeworld(Player::White), Some(SystemId(Key(24))));
+    assert_eq!(game.homeworld(Player::Black), None);
+    for m in ["select medium yellow", "select medium green", "select small yellow"] {
+        assert!(game.process_move(m.parse().unwrap()));
+    }
+    assert_eq!(game.homeworld(Player::Black), Some(SystemId(Key(12))));
+    assert_eq!(game.systems(), vec![SystemId(Key(12)), SystemId(Key(24))]);
+
+    // Four reds at White's single-star home: Black's catastrophe takes the star with them
+    let mut game = Game::from_system_list(
+        "white: r3 | g1 | r1 r2 r2
+         black: y2 g1 | | b3
+         turn: black",
+    )
+    .unwrap();
+    let black = game.homeworld(Player::Black);
+    assert!(game.homeworld(Player::White).is_some());
+    assert!(game.process_move(Move::Catastrophe(game.legal_catastrophes()[0])));
+    assert_eq!(game.homeworld(Player::White), None);
+    assert_eq!(game.homeworld(Player::Black), black);
+    assert_eq!(game.game_result(), GameResult::Win(Player::Black));
+}
+
+#[test]
+fn test_to_compact() {
+    let mut game = Game::new();
+    assert_eq!(game.to_compact(), "W:- B:- | ; w");
+
+    let moves = [
+        "select large green",
+        "select small blue",
+        "select large yellow",
+        "select medium yellow",
+        "select medium green",
+        "select small yellow",
+    ];
+    for m in moves {
+        assert!(game.process_move(m.parse().unwrap()));
+    }
+    let start = "W:(g3,b1)[y3] B:(y2,g2)[y1*] | ; w";
+    assert_eq!(game.to_compact(), start);
+
+    // The same position reached again, or read back from its system list, prints the same
+    assert_eq!(Game::from_moves(&moves.map(|m| m.parse().unwrap())).unwrap().to_compact(), start);
+    assert_eq!(Game::from_system_list(&game.to_system_list()).unwrap().to_compact(), start);
+
+    // Colonies follow the homeworlds, with Black's ships starred
+    let game = Game::from_system_list(
+        "white: g3 b1 | y3 |\nblack: y2 g2 | | y1\nc: b2 | g1 | r1\nturn: black",
+    )
+    .unwrap();
+    assert_eq!(game.to_compact(), "W:(g3,b1)[y3] B:(y2,g2)[y1*] | S1:(b2)[r1*,g1] ; b");
+}
+
+#[test]
+fn test_homeworld_health() {
+    // Two stars and a fleet; the enemy red shares no color with the stars
+    let game = Game::from_system_list(
+        "white: g3 b1 | y3 g2 | r1
+         black: y2 g1 | | r3
+         turn: white",
+    )
+    .unwrap();
+    let health = game.homeworld_health(Player::White);
+    assert_eq!(
+        health,
+        HomeworldHealth {
+            stars: 2,
+            ships: 2,
+            overpopulated: false,
+            threats: 0,
+        }
+    );
+
+    // One star, one ship, and three enemy reds matching it: a single attack or
+    // catastrophe ends the game
+    let game = Game::from_system_list(
+        "white: r3 | g1 | r1 r2 r2
+         black: y2 g1 | | b3
+         turn: black",
+    )
+    .unwrap();
+    let health = game.homeworld_health(Player::White);
+    assert_eq!(
+        health,
+        HomeworldHealth {
+            stars: 1,
+            ships: 1,
+            overpopulated: true,
+            threats: 3,
+        }
+    );
+    assert_eq!(game.homeworld_health(Player::Black).threats, 0);
+}
+
+#[test]
+fn test_game_phase() {
+    let mut game = Game::new();
+    assert_eq!(game.game_phase(), GamePhase::Setup);
+    for m in [
+        "select large green",
+        "select small blue",
+        "select large yellow",
+        "select medium yellow",
+        "select medium green",
+    ] {
+        assert!(game.process_move(m.parse().unwrap()), "{}", m);
+        assert_eq!(game.game_phase(), GamePhase::Setup);
+    }
+    assert!(game.process_move("select small yellow".parse().unwrap()));
+    assert_eq!(game.game_phase(), GamePhase::Opening);
+
+    // Plenty of ships on both sides
+    let game = Game::from_system_list(
+        "white: g3 b1 | y3 g2 r1 b2 |
+         black: y2 g1 | | b3 r2 g1 y1
+         s1: r3 | y1 | r1
+         turn: white",
+    )
+    .unwrap();
+    assert_eq!(game.game_phase(), GamePhase::Midgame);
+
+    // White is down to one ship against Black's four
+    let game = Game::from_system_list(
+        "white: g3 b1 | y1 |
+         black: y2 g1 | | b3 r2 g1 y3
+         turn: white",
+    )
+    .unwrap();
+    assert_eq!(game.game_phase(), GamePhase::Endgame);
+}
+
+#[test]
+fn test_sacrifice_remaining() {
+    // White's large green buys three constructions for its red and blue ships
+    let mut game = Game::from_system_list(
+        "white: y3 b1 | g3 r1 b2 |
+         black: y2 | | g2
+         turn: white",
+    )
+    .unwrap();
+    assert_eq!(game.sacrifice_remaining(), 0);
+    assert!(game.process_move("sacrifice 24".parse().unwrap()));
+    for (m, left) in [("construct 0", 3), ("construct 0", 2), ("construct 30", 1)] {
+        assert_eq!(game.sacrifice_remaining(), left);
+        assert_eq!(game.to_move(), Player::White);
+        assert!(game.process_move(m.parse().unwrap()), "{}", m);
+    }
+
+    // The third action handed the turn over, and there is no fourth
+    assert_eq!(game.sacrifice_remaining(), 0);
+    assert_eq!(game.to_move(), Player::Black);
+    assert!(game.pending_actions().is_none());
+    assert!(!game.process_move("construct 0".parse().unwrap()));
+    assert_eq!(game.bank_count(Size::Small, Color::Red), 0);
+}
+
+#[test]
+fn test_must_pass() {
+    // White's small red has nothing to attack in either of White's systems
+    let mut game = Game::from_system_list(
+        "white: g3 b1 | y3 r1 |
+         black: y2 | | g2
+         turn: white",
+    )
+    .unwrap();
+    assert!(!game.must_pass());
+    assert!(game.process_move(Move::Sacrifice(Key(0))));
+    assert!(game.must_pass());
+    assert!(!game.process_move(Move::Attack(Key(21))));
+    assert!(!game.process_move(Move::Construct(Key(15))));
+    assert!(game.process_move(Move::Pass));
+    assert_eq!(game.to_move(), Player::Black);
+    assert!(!game.must_pass());
+}
+
+#[test]
+fn test_sibling_order() {
+    let mut game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium green", "select medium red", "select small green"],
+    );
+    for m in [
+        "construct 15",  // White's 9 follows 15
+        "construct 18",  // Black's 19 follows 18
+        "construct 9",   // White's 10 follows its builder 9, ahead of 15
+        "pass",
+        "moveinit 9",    // The lower of the identical pair is the one named
+        "movefinish 21", // 9 lands right after Black's homeworld child 18
+    ] {
+        assert!(game.process_move(m.parse().unwrap()), "{}", m);
+    }
+    let ring = |start: u8| {
+        let keys: Vec<u8> = game.board.sibling_iter(Key(start)).map(|(_, k)| k.0).collect();
+        keys
+    };
+    assert_eq!(ring(15), [15, 10]);
+    assert_eq!(ring(10), [10, 15]);
+    assert_eq!(ring(18), [18, 9, 19]);
+    assert_eq!(ring(19), [19, 18, 9]);
+
+    // Rendering ignores the ring and goes by key
+    assert!(game.to_system_list().contains("black: g2 r2 | y1 | g1 g1"));
+}
+
+#[test]
+fn test_transform_relinks() {
+    // White's blue star grants transforms. The large yellow is alone at home, so it is
+    // both the star's child and its own sibling.
+    let mut game =
+        Game::from_system_list("white: b3 g1 | y3 |\nblack: b2 y1 | | g3\nturn: white").unwrap();
+    let yellow = make_key(Color::Yellow, Size::Large, 0).unwrap();
+    assert!(game.process_move(Move::Transform(yellow, Color::Red)));
+    assert_eq!(game.check_invariants(), Ok(()));
+    let red = make_key(Color::Red, Size::Large, 0).unwrap();
+    let home = game.homeworld(Player::White).unwrap();
+    assert_eq!(game.piece_at(yellow.0), Some(PieceInfo::Bank));
+    assert_eq!(
+        game.piece_at(red.0),
+        Some(PieceInfo::Ship {
+            size: Size::Large,
+            color: Color::Red,
+            owner: Player::White,
+            system: home,
+        })
+    );
+    assert_eq!(game.fleet_distribution(Player::White), vec![(home, 1)]);
+
+    // In a ring of two, the other ship now links to the new piece
+    let mut game =
+        Game::from_system_list("white: b3 g1 | y3 r1 |\nblack: b2 y1 | | g3\nturn: white").unwrap();
+    assert!(game.process_move(Move::Transform(yellow, Color::Green)));
+    assert_eq!(game.check_invariants(), Ok(()));
+    let green = make_key(Color::Green, Size::Large, 1).unwrap(); // Black holds the first
+    let ring: Vec<Key> = game.board.sibling_iter(green).map(|(_, key)| key).collect();
+    assert_eq!(ring, [green, Key(0)]);
+}
+
+#[test]
+fn test_move_finish_arrives() {
+    let mut game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    assert!(game.process_move("construct 15".parse().unwrap()));
+    assert!(game.process_move(Move::Pass));
+
+    // Mid-flight the game can't be judged; once the ship lands it can
+    assert!(game.process_move("moveinit 15".parse().unwrap()));
+    assert!(game.moving_piece == KeyMaybe::some(Key(15)));
+    assert!(game.process_move("movefinish 12".parse().unwrap()));
+    assert!(game.moving_piece.is_none());
+    assert_eq!(game.to_move(), Player::Black);
+    assert_eq!(game.game_result(), GameResult::Ongoing);
+    // Black has no flight of White's to finish
+    let moves = game.legal_moves_of_kind(MoveKind::Move);
+    assert!(moves.iter().all(|&i| matches!(MOVES[i], Move::MoveInit(_))));
+}
+
+#[test]
+fn test_pass_abandons_movement() {
+    let mut game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    );
+    let home = game.homeworld(Player::White).unwrap();
+    assert!(game.process_move("moveinit 15".parse().unwrap()));
+    assert!(game.process_move(Move::Pass));
+
+    // The ship never left, and Black's turn starts with nothing in flight
+    assert!(game.moving_piece.is_none());
+    assert_eq!(game.to_move(), Player::Black);
+    assert!(matches!(game.piece_at(15), Some(PieceInfo::Ship { system, .. }) if system == home));
+    assert_eq!(game.game_result(), GameResult::Ongoing);
+}
+
+#[test]
+fn test_catastrophe_stars() {
+    let position = |white: &str| {
+        let mut game = Game::from_system_list(&format!(
+            "white: {}\nblack: r2 y1 | | r3\nturn: white",
+            white
+        ))
+        .unwrap();
+        game.set_catastrophe_mode(CatastropheMode::Manual);
+        game
+    };
+    let green = |copy| make_key(Color::Green, Size::Small, copy).unwrap();
+
+    // Only the lowest ship of the color may name the catastrophe
+    let mut game = position("g3 b1 | g1 g1 g2 y3 |");
+    assert!(!game.clone().process_move(Move::Catastrophe(green(1))));
+
+    // The first star collapses and the second takes over the system and homeworld
+    assert!(game.process_move(Move::Catastrophe(green(0))));
+    assert_eq!(game.check_invariants(), Ok(()));
+    let blue = make_key(Color::Blue, Size::Small, 0).unwrap();
+    assert_eq!(game.homeworld(Player::White), Some(SystemId(blue)));
+    assert_eq!(game.homeworld_health(Player::White).stars, 1);
+    assert_eq!(game.fleet_distribution(Player::White), vec![(SystemId(blue), 1)]);
+
+    // Both stars collapse and the homeworld goes with them
+    let green_medium = make_key(Color::Green, Size::Medium, 0).unwrap();
+    let mut game = position("g3 g1 | g2 g2 y3 |");
+    assert!(game.process_move(Move::Catastrophe(green_medium)));
+    assert_eq!(game.check_invariants(), Ok(()));
+    assert_eq!(game.homeworld(Player::White), None);
+    assert_eq!(game.game_result(), GameResult::Win(Player::Black));
+    assert_eq!(game.bank_count(Size::Large, Color::Green), COPY_COUNT);
+
+    // The second star collapses and the first keeps the system
+    let mut game = position("b3 g1 | g2 g2 g3 y3 |");
+    let first = game.homeworld(Player::White);
+    assert!(game.process_move(Move::Catastrophe(green_medium)));
+    assert_eq!(game.check_invariants(), Ok(()));
+    assert_eq!(game.homeworld(Player::White), first);
+    assert_eq!(game.homeworld_health(Player::White).stars, 1);
+    assert_eq!(game.fleet_distribution(Player::White), vec![(first.unwrap(), 1)]);
+}
+
+#[test]
+fn test_catastrophe_cascade() {
+    // Black's homeworld holds four yellows (star included) and four green ships. The
+    // yellow collapse comes first and takes a star with it, so the green one has to
+    // find the system under whichever star survives.
+    for stars in ["y2 b1", "b1 y2"] {
+        let mut game = Game::from_system_list(&format!(
+            "white: g3 r1 | b3 |
+             black: {} | y1 y3 g1 g2 | y1 g1 g3 r1
+             turn: white",
+            stars
+        ))
+        .unwrap();
+        assert_eq!(game.overpopulations().len(), 2);
+        let before = game.catastrophes;
+        assert!(game.process_move(Move::Pass));
+        assert_eq!(game.catastrophes - before, 2, "{}", stars);
+        assert_eq!(game.check_invariants(), Ok(()), "{}", stars);
+        assert!(game.overpopulations().is_empty());
+
+        // Black's small red is all that is left, around the blue star
+        let health = game.homeworld_health(Player::Black);
+        assert_eq!((health.stars, health.ships), (1, 1), "{}", stars);
+        assert_eq!(game.fleet_distribution(Player::White).len(), 1);
+        assert_eq!(game.game_result(), GameResult::Ongoing);
+        assert!(game.process_move(Move::Pass));
+        assert_eq!(game.check_invariants(), Ok(()));
+    }
+}
+
+#[test]
+fn test_catastrophe_mode() {
+    for mode in [CatastropheMode::Auto, CatastropheMode::Manual] {
+        let mut game = setup_game(
+            ["select large green", "select small blue", "select large yellow"],
+            ["select medium yellow", "select medium green", "select small yellow"],
+        );
+        game.set_catastrophe_mode(mode);
+        // White ends a turn with four yellows in Black's homeworld
+        for m in [
+            "construct 15",
+            "pass",
+            "construct 10",
+            "pass",
+            "moveinit 15",
+            "movefinish 12",
+            "pass",
+            "moveinit 10",
+            "movefinish 12",
+        ] {
+            assert!(game.process_move(m.parse().unwrap()), "{}", m);
+        }
+        match mode {
+            CatastropheMode::Auto => {
+                assert!(game.overpopulations().is_empty());
+                assert_eq!(game.game_result(), GameResult::Win(Player::White));
+            }
+            CatastropheMode::Manual => {
+                // The overpopulation survives the turn boundary and Black's pass
+                assert_eq!(game.overpopulations(), vec![Key(9)]);
+                assert_eq!(game.game_result(), GameResult::Ongoing);
+                assert!(game.process_move(Move::Pass));
+                assert_eq!(game.overpopulations(), vec![Key(9)]);
+
+                // Until White collapses it
+                assert!(game.process_move(Move::Catastrophe(Key(9))));
+                assert!(game.overpopulations().is_empty());
+                assert_eq!(game.game_result(), GameResult::Win(Player::White));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_check_invariants() {
+    let game = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select large red", "select medium blue", "select large yellow"],
+    );
+    assert_eq!(game.check_invariants(), Ok(()));
+    let ship = Key(15); // White's large yellow ship
+    let wstar = game.wstar.get().unwrap();
+
+    // A ship whose parent is a bank piece
+    let mut bad = game.clone();
+    bad.board[ship] = Piece::Ship(Ship {
+        parent: Key(0),
+        sibling: ship,
+        player: Player::White,
+    });
+    assert_eq!(
+        bad.check_invariants(),
+        Err(InvariantError::ShipParentNotStar { ship })
+    );
+
+    // A ship whose sibling is not a ship
+    let mut bad = game.clone();
+    bad.board[ship] = Piece::Ship(Ship {
+        parent: wstar,
+        sibling: Key(0),
+        player: Player::White,
+    });
+    assert_eq!(
+        bad.check_invariants(),
+        Err(InvariantError::ShipSiblingNotShip { ship })
+    );
+
+    // A ring that loops without returning home
+    let mut bad = game.clone();
+    let other = Key(9);
+    bad.board[ship] = Piece::Ship(Ship {
+        parent: wstar,
+        sibling: other,
+        player: Player::White,
+    });
+    bad.board[other] = Piece::Ship(Ship {
+        parent: wstar,
+        sibling: other,
+        player: Player::White,
+    });
+    assert_eq!(
+        bad.check_invariants(),
+        Err(InvariantError::ShipSiblingCycle { ship })
+    );
+
+    // A binary second whose first doesn't point back
+    let mut bad = game.clone();
+    bad.board[wstar] = Piece::BinaryFirst {
+        child: KeyMaybe::some(ship),
+        sibling: KeyMaybe::none(),
+    };
+    assert!(matches!(
+        bad.check_invariants(),
+        Err(InvariantError::BinaryUnpaired { .. })
+    ));
+
+    // A star whose child is gone
+    let mut bad = game.clone();
+    bad.board[ship] = Piece::Bank;
+    assert_eq!(
+        bad.check_invariants(),
+        Err(InvariantError::StarChildNotShip { star: wstar })
+    );
+
+    // A homeworld pointer to a bank piece
+    let mut bad = game.clone();
+    bad.wstar = KeyMaybe::some(Key(0));
+    assert_eq!(
+        bad.check_invariants(),
+        Err(InvariantError::HomeworldNotStar { player: Player::White })
+    );
+}
+
+#[test]
+fn test_legal_catastrophes() {
+    // Green is overpopulated at White's home and red out in the middle
+    let position = "white: g3 b1 | y3 g1 g2 g2 |\nblack: b2 y1 | | g3 y2\n\
+                    mid: r1 | r2 r3 | r1\nturn: white";
+    let mut game = Game::from_system_list(position).unwrap();
+    game.set_catastrophe_mode(CatastropheMode::Manual);
+    assert_eq!(game.legal_catastrophes(), vec![Key(1), Key(18)]);
+    assert_eq!(game.legal_catastrophes(), game.overpopulations());
+
+    // Free by default: White names both and still has the turn
+    let mut free = game.clone();
+    assert!(free.process_move(Move::Catastrophe(Key(1))));
+    assert_eq!(free.legal_catastrophes(), vec![Key(18)]);
+    assert!(free.process_move(Move::Catastrophe(Key(18))));
+    assert!(free.legal_catastrophes().is_empty());
+    assert_eq!(free.to_move(), Player::White);
+
+    // None while a ship is in flight, though the overpopulations still stand
+    let mut flying = game.clone();
+    assert!(flying.process_move("moveinit 15".parse().unwrap()));
+    assert!(flying.legal_catastrophes().is_empty());
+    assert_eq!(flying.overpopulations().len(), 2);
+
+    // Costing an action, the first catastrophe ends White's turn
+    let mut costly = game.clone();
+    costly.set_catastrophe_cost(CatastropheCost::Action);
+    assert_eq!(costly.legal_catastrophes(), vec![Key(1), Key(18)]);
+    assert!(costly.process_move(Move::Catastrophe(Key(18))));
+    assert_eq!(costly.to_move(), Player::Black);
+    assert_eq!(costly.legal_catastrophes(), vec![Key(1)]);
+}
+
+#[test]
+fn test_perft() {
+    // Counts taken before process_move dispatched on the turn phase, which must not
+    // change what is legal
+    assert_eq!(Game::new().perft(1), 12);
+    assert_eq!(Game::new().perft(3), 1728);
+    let opened = setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium green", "select medium yellow", "select small red"],
+    );
+    assert_eq!(opened.perft(6), 459);
+    // A colony to sacrifice into and fly between, so the counts run through every phase
+    let sacrifice = Game::from_system_list(
+        "white: g3 b1 | y1 r2 |\nblack: y2 b2 | | g2 r1\nmid: y1 | y3 g1 | b3\nturn: white",
+    )
+    .unwrap();
+    assert_eq!(sacrifice.perft(1), 13);
+    assert_eq!(sacrifice.perft(4), 4966);
+}
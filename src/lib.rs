@@ -0,0 +1,10 @@
+pub mod driver;
+pub mod eval;
+pub mod fuzz;
+pub mod game;
+pub mod selfplay;
+pub mod session;
+pub mod stats;
+pub mod tablebase;
+mod util;
+mod wire;
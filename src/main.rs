@@ -1,3 +1 @@
-mod board;
-
 fn main() {}
@@ -0,0 +1,74 @@
+// Self-play game records for generating training data
+use crate::util::wyhash64;
+use crate::game::{Game, GameResult, MOVES};
+
+// Function to play a game from the start, recording each position (as it stood when
+// the move was chosen), the index of the move played there, and the game's final
+// result. `policy` picks the moves and must return a legal move index. With a
+// `setup_seed`, the homeworld setup is drawn at random from it instead, so a
+// deterministic policy still sees varied games. Stops after `max_moves` moves, leaving
+// the result `Ongoing` if the game wasn't decided by then.
+pub fn self_play<F: FnMut(&Game) -> usize>(
+    mut policy: F,
+    setup_seed: Option<u64>,
+    max_moves: usize,
+) -> Vec<(Game, usize, GameResult)> {
+    let mut game = Game::new();
+    let mut records = Vec::new();
+    for step in 0..max_moves {
+        if game.game_result() != GameResult::Ongoing {
+            break;
+        }
+        let setup = game.setup_options();
+        let i = match setup_seed {
+            Some(seed) if !setup.is_empty() => {
+                let m = setup[(wyhash64(seed, step as u64) % setup.len() as u64) as usize];
+                MOVES.iter().position(|&v| v == m).unwrap()
+            }
+            _ => policy(&game),
+        };
+        records.push((game.clone(), i, GameResult::Ongoing));
+        assert!(game.process_move_idx(i), "policy chose illegal move {}", i);
+    }
+    let result = game.game_result();
+    for record in records.iter_mut() {
+        record.2 = result;
+    }
+    records
+}
+
+#[test]
+fn test_self_play() {
+    // Always play the first legal move
+    let first = |game: &Game| game.legal_moves()[0];
+    let records = self_play(first, Some(7), 500);
+    assert!(!records.is_empty());
+    let result = records[0].2;
+    assert_ne!(result, GameResult::Ongoing);
+    assert!(records.iter().all(|r| r.2 == result));
+
+    // Each record is the position before its move, and replaying them reaches the end
+    let mut game = Game::new();
+    for (position, i, _) in records.iter() {
+        assert_eq!(position.hash(), game.hash());
+        assert_eq!(position.ply(), game.ply());
+        assert!(game.process_move_idx(*i));
+    }
+    assert_eq!(game.game_result(), result);
+
+    // The seed varies the setup; a short cap leaves the game undecided
+    let other = self_play(first, Some(8), 500);
+    assert_ne!(other[0].1, records[0].1);
+    let short = self_play(first, Some(7), 3);
+    assert_eq!(short.len(), 3);
+    assert!(short.iter().all(|r| r.2 == GameResult::Ongoing));
+
+    // Without a seed the policy sets up the homeworlds too
+    let own = self_play(first, None, 6);
+    let mut game = Game::new();
+    for (position, i, _) in own.iter() {
+        assert_eq!(*i, position.legal_moves()[0]);
+        assert!(game.process_move_idx(*i));
+    }
+    assert!(game.setup_options().is_empty());
+}
@@ -0,0 +1,501 @@
+// A game as played between two people: the rules position plus everything decided
+// off the board (resignations, draw offers) and the record of how play got here
+use crate::game::{Game, GameResult, Move, Player, MOVES};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[cfg(test)]
+use crate::game::setup_game;
+
+// How many times a position may be reached before the game is drawn
+const REPETITION_DRAW: usize = 3;
+
+// Why a session was won
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WinReason {
+    HomeworldDestroyed, // The loser's homeworld lost its stars or their ships there
+    Resignation,
+}
+
+// Why a session was drawn
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DrawReason {
+    MutualDestruction, // Both homeworlds fell at once
+    Agreement,         // A draw offer was accepted
+    Repetition,        // The position came up for the third time
+    PlyLimit,          // The game ran past the session's ply limit
+    Stalemate,         // Neither player can do anything but pass
+}
+
+// The arbiter's verdict on a session, see `Session::adjudicate`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Adjudication {
+    Ongoing,
+    Win(Player, WinReason),
+    Draw(DrawReason),
+}
+
+// A variant rule that vetoes moves the engine would otherwise allow, by returning false.
+// Shared and thread-safe so a `Session` can be cloned and handed between threads.
+pub type MoveFilter = Arc<dyn Fn(&Game, Move) -> bool + Send + Sync>;
+
+// Notes a study tool attaches to a position with `Session::annotate`
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Annotation {
+    pub comment: String,
+    pub marked_moves: Vec<usize>, // Move table indices of the candidates worth a look
+}
+
+#[derive(Clone)]
+pub struct Session {
+    game: Game,
+    history: Vec<Move>,             // Every accepted move, in order
+    positions: Vec<u64>,            // Hash of each position reached, the start included
+    resolution: Option<GameResult>, // An outcome agreed off the board
+    draw_offer: Option<Player>,
+    auto_pass: bool, // Whether forced passes are played without waiting for the player
+    annotations: HashMap<u64, Annotation>, // By position hash, so transpositions share notes
+    ply_limit: Option<u32>, // Plies after which the game is drawn, counted as `Game::ply` does
+    move_filter: Option<MoveFilter>, // Handicap or variant rule consulted before the engine
+    stalemate: bool, // Whether both players are stuck, rechecked only when the turn passes
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Session {
+    // Constructor method to start a session from the empty board
+    pub fn new() -> Self {
+        Self::from_game(Game::new())
+    }
+
+    // Constructor method to start a session from a position, with no history before it
+    pub fn from_game(game: Game) -> Self {
+        Self {
+            positions: vec![game.hash()],
+            stalemate: Self::stalemated(&game),
+            game,
+            history: Vec::new(),
+            resolution: None,
+            draw_offer: None,
+            auto_pass: false,
+            annotations: HashMap::new(),
+            ply_limit: None,
+            move_filter: None,
+        }
+    }
+
+    // Function to check whether neither player can do anything but pass
+    fn stalemated(game: &Game) -> bool {
+        game.is_stuck(Player::White) && game.is_stuck(Player::Black)
+    }
+
+    // Method to get the rules position
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    // Method to get the moves played in this session, in order
+    pub fn history(&self) -> &[Move] {
+        &self.history
+    }
+
+    // Method to get the hash of every position reached, the start included, for
+    // `eval::evaluate_with_history`
+    pub fn positions(&self) -> &[u64] {
+        &self.positions
+    }
+
+    // Method to choose whether a player left with nothing but `Move::Pass` passes on
+    // their own, so a UI never offers a menu with a single entry
+    pub fn set_auto_pass(&mut self, auto_pass: bool) {
+        self.auto_pass = auto_pass;
+    }
+
+    // Method to draw the game once `Game::ply` reaches `limit`, or never with None
+    pub fn set_ply_limit(&mut self, limit: Option<u32>) {
+        self.ply_limit = limit;
+    }
+
+    // Method to get the ply at which the game is drawn, if any
+    pub fn ply_limit(&self) -> Option<u32> {
+        self.ply_limit
+    }
+
+    // Method to install a variant rule every move must pass, or remove it with None
+    pub fn set_move_filter(&mut self, filter: Option<MoveFilter>) {
+        self.move_filter = filter;
+    }
+
+    // Method to check a move against the installed variant rule, if any
+    fn allowed(&self, m: Move) -> bool {
+        self.move_filter.as_ref().is_none_or(|filter| filter(&self.game, m))
+    }
+
+    // Method to list the move table indices of the legal moves the variant rule allows
+    pub fn legal_moves(&self) -> Vec<usize> {
+        let mut moves = self.game.legal_moves();
+        moves.retain(|&i| self.allowed(MOVES[i]));
+        moves
+    }
+
+    // Method to play a move, recording it along with the forced pass that follows when
+    // auto-pass is on. A decided session is frozen.
+    pub fn play(&mut self, m: Move) -> bool {
+        if self.adjudicate() != Adjudication::Ongoing {
+            return false;
+        }
+        let player = self.game.to_move();
+        if !self.allowed(m) || !self.game.process_move(m) {
+            return false;
+        }
+        self.history.push(m);
+        self.positions.push(self.game.hash());
+        // Stalemate is judged between turns, so actions within one skip the scan
+        if self.game.to_move() != player {
+            self.stalemate = Self::stalemated(&self.game);
+        }
+        // Playing on instead of accepting declines the opponent's draw offer
+        if self.draw_offer == Some(player.inv()) {
+            self.draw_offer = None;
+        }
+        if self.auto_pass && self.adjudicate() == Adjudication::Ongoing && self.game.must_pass() {
+            self.play(Move::Pass);
+        }
+        true
+    }
+
+    // Method to attach notes to the current position, replacing any it already had.
+    // Annotating is allowed after the game is decided, for post-game study.
+    pub fn annotate(&mut self, comment: &str, marked_moves: Vec<usize>) {
+        let annotation = Annotation {
+            comment: comment.to_string(),
+            marked_moves,
+        };
+        self.annotations.insert(self.game.hash(), annotation);
+    }
+
+    // Method to look up the notes on a position by its hash (see `Game::hash`)
+    pub fn annotation(&self, hash: u64) -> Option<&Annotation> {
+        self.annotations.get(&hash)
+    }
+
+    // Method to get the notes on the current position
+    pub fn current_annotation(&self) -> Option<&Annotation> {
+        self.annotation(self.game.hash())
+    }
+
+    // Method to count how many times the current position has been reached, this time included
+    pub fn repetitions(&self) -> usize {
+        let current = self.game.hash();
+        self.positions.iter().filter(|&&h| h == current).count()
+    }
+
+    // Method to get the outcome, whether decided on the board or off it. Draws the
+    // session's own rules call (repetition, ply limit, stalemate) are left to `adjudicate`.
+    pub fn result(&self) -> GameResult {
+        self.resolution.unwrap_or_else(|| self.game.game_result())
+    }
+
+    // Method to rule on whether the game is over and why, the one verdict a server
+    // should trust. An outcome agreed off the board comes first, then the board's own,
+    // then the session's draw rules: a third repetition, the ply limit and stalemate.
+    pub fn adjudicate(&self) -> Adjudication {
+        match self.result() {
+            GameResult::Win(player) => {
+                return Adjudication::Win(player, WinReason::HomeworldDestroyed)
+            }
+            GameResult::Resignation(player) => {
+                return Adjudication::Win(player, WinReason::Resignation)
+            }
+            GameResult::Draw => return Adjudication::Draw(DrawReason::MutualDestruction),
+            GameResult::AgreedDraw => return Adjudication::Draw(DrawReason::Agreement),
+            GameResult::Ongoing => {}
+        }
+        if self.repetitions() >= REPETITION_DRAW {
+            Adjudication::Draw(DrawReason::Repetition)
+        } else if self.ply_limit.is_some_and(|limit| self.game.ply() >= limit) {
+            Adjudication::Draw(DrawReason::PlyLimit)
+        } else if self.stalemate {
+            Adjudication::Draw(DrawReason::Stalemate)
+        } else {
+            Adjudication::Ongoing
+        }
+    }
+
+    // Method to concede the game on behalf of `player`, whoever is to move
+    pub fn resign(&mut self, player: Player) {
+        if self.adjudicate() == Adjudication::Ongoing {
+            self.resolution = Some(GameResult::Resignation(player.inv()));
+        }
+    }
+
+    // Method to offer a draw on behalf of `player`. The offer stands until the
+    // opponent accepts it or makes a move.
+    pub fn offer_draw(&mut self, player: Player) -> bool {
+        if self.adjudicate() != Adjudication::Ongoing {
+            return false;
+        }
+        self.draw_offer = Some(player);
+        true
+    }
+
+    // Method to accept the opponent's standing draw offer on behalf of `player`
+    pub fn accept_draw(&mut self, player: Player) -> bool {
+        if self.adjudicate() != Adjudication::Ongoing || self.draw_offer != Some(player.inv()) {
+            return false;
+        }
+        self.draw_offer = None;
+        self.resolution = Some(GameResult::AgreedDraw);
+        true
+    }
+}
+
+#[cfg(test)]
+fn setup_session() -> Session {
+    Session::from_game(setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium yellow", "select medium green", "select small yellow"],
+    ))
+}
+
+#[test]
+fn test_resign() {
+    let mut session = setup_session();
+    assert!(session.play("construct 15".parse().unwrap()));
+
+    // White may resign on Black's turn
+    session.resign(Player::White);
+    assert_eq!(session.result(), GameResult::Resignation(Player::Black));
+    assert!(!session.play(Move::Pass));
+    assert_eq!(session.history().len(), 1);
+
+    // Nothing changes the result afterwards
+    session.resign(Player::Black);
+    assert!(!session.offer_draw(Player::Black));
+    assert_eq!(session.result(), GameResult::Resignation(Player::Black));
+
+    // The position itself is still open
+    assert_eq!(session.game().game_result(), GameResult::Ongoing);
+}
+
+#[test]
+fn test_draw_offer() {
+    let mut session = setup_session();
+
+    // Nobody can accept their own offer, and playing on declines it
+    assert!(session.offer_draw(Player::White));
+    assert!(!session.accept_draw(Player::White));
+    assert!(session.play("construct 15".parse().unwrap()));
+    assert!(session.accept_draw(Player::Black));
+    assert_eq!(session.result(), GameResult::AgreedDraw);
+    assert!(!session.play(Move::Pass));
+
+    let mut session = setup_session();
+    assert!(session.offer_draw(Player::White));
+    assert!(session.play("construct 15".parse().unwrap()));
+    assert!(session.play(Move::Pass));
+    assert!(!session.accept_draw(Player::Black));
+    assert_eq!(session.result(), GameResult::Ongoing);
+}
+
+#[test]
+fn test_repetitions() {
+    let mut session = setup_session();
+    assert!(session.play("construct 15".parse().unwrap()));
+    assert!(session.play(Move::Pass));
+    assert_eq!(session.repetitions(), 1);
+
+    // White's large yellow flies out and back while Black waits
+    for m in ["moveinit 15", "movefinish 12", "pass", "moveinit 15", "movefinish 24", "pass"] {
+        assert!(session.play(m.parse().unwrap()), "{}", m);
+    }
+    assert_eq!(session.repetitions(), 2);
+    assert_eq!(session.history().len(), 8);
+}
+
+#[test]
+fn test_game_searches_alone() {
+    // The rules position owns no heap data, so cloning it for search allocates nothing
+    assert!(!std::mem::needs_drop::<Game>());
+
+    // Depth-two search straight on the position, no session involved
+    let game = setup_session().game().clone();
+    let mut leaves = 0;
+    for i in game.legal_moves() {
+        let mut child = game.clone();
+        assert!(child.process_move_idx(i));
+        if child.is_terminal() {
+            leaves += 1;
+            continue;
+        }
+        leaves += child.legal_moves().len();
+    }
+    assert!(leaves > 0);
+    assert!(!game.is_terminal());
+}
+
+#[test]
+fn test_auto_pass() {
+    // White's small red has nothing to attack, so sacrificing it leaves only a pass
+    let position = "white: g3 b1 | y3 r1 |\nblack: y2 | | g2\nturn: white";
+    let sacrifice: Move = "sacrifice 0".parse().unwrap();
+
+    let mut session = Session::from_game(Game::from_system_list(position).unwrap());
+    assert!(session.play(sacrifice));
+    assert_eq!(session.game().to_move(), Player::White);
+
+    let mut session = Session::from_game(Game::from_system_list(position).unwrap());
+    session.set_auto_pass(true);
+    assert!(session.play(sacrifice));
+    assert_eq!(session.game().to_move(), Player::Black);
+    assert_eq!(session.history(), &[sacrifice, Move::Pass]);
+}
+
+#[test]
+fn test_annotations() {
+    let mut session = Session::from_game(setup_game(
+        ["select large green", "select small blue", "select large yellow"],
+        ["select medium green", "select medium yellow", "select small red"],
+    ));
+    let play = |session: &mut Session, moves: &[&str]| {
+        for m in moves {
+            assert!(session.play(m.parse().unwrap()), "{}", m);
+        }
+    };
+
+    // Each side builds one ship
+    play(&mut session, &["construct 15", "construct 0"]);
+    assert!(session.current_annotation().is_none());
+    let moves = session.game().legal_moves()[..2].to_vec();
+    session.annotate("both fleets doubled", moves.clone());
+    let hash = session.game().hash();
+
+    // The notes belong to the position, so they're back when both sides pass to it again
+    play(&mut session, &["pass"]);
+    assert!(session.current_annotation().is_none());
+    play(&mut session, &["pass"]);
+    assert_eq!(session.game().hash(), hash);
+    let note = session.current_annotation().unwrap();
+    assert_eq!(note.comment, "both fleets doubled");
+    assert_eq!(note.marked_moves, moves);
+
+    // Annotating again replaces the notes
+    session.annotate("revised", Vec::new());
+    assert_eq!(session.annotation(hash).unwrap().comment, "revised");
+    assert!(session.annotation(0).is_none());
+}
+
+#[test]
+fn test_adjudicate() {
+    let win = |player| Adjudication::Win(player, WinReason::HomeworldDestroyed);
+
+    // White's large red takes Black's last ship at home
+    let position = "white: g3 b1 | y3 |\nblack: r2 b1 | r3 | g1\nturn: white";
+    let mut session = Session::from_game(Game::from_system_list(position).unwrap());
+    assert_eq!(session.adjudicate(), Adjudication::Ongoing);
+    assert!(session.play("attack 18".parse().unwrap()));
+    assert_eq!(session.adjudicate(), win(Player::White));
+
+    // Resignation and agreement
+    let mut session = setup_session();
+    session.resign(Player::Black);
+    let resigned = Adjudication::Win(Player::White, WinReason::Resignation);
+    assert_eq!(session.adjudicate(), resigned);
+    let mut session = setup_session();
+    assert!(session.offer_draw(Player::White));
+    assert!(session.accept_draw(Player::Black));
+    assert_eq!(session.adjudicate(), Adjudication::Draw(DrawReason::Agreement));
+
+    // White's large yellow flies out and back twice; each return hands Black the
+    // position they had after the construction, the second time for the third time
+    let mut session = setup_session();
+    assert!(session.play("construct 15".parse().unwrap()));
+    let flight = ["moveinit 15", "movefinish 12", "pass", "moveinit 15", "movefinish 24"];
+    for m in ["pass"].iter().chain(&flight).chain(&["pass"]).chain(&flight) {
+        assert_eq!(session.adjudicate(), Adjudication::Ongoing);
+        assert!(session.play(m.parse().unwrap()), "{}", m);
+    }
+    assert_eq!(session.repetitions(), 3);
+    assert_eq!(session.adjudicate(), Adjudication::Draw(DrawReason::Repetition));
+    assert!(!session.play(Move::Pass));
+
+    // Setup takes six plies, so a limit of eight allows one turn each
+    let mut session = setup_session();
+    session.set_ply_limit(Some(8));
+    assert!(session.play("construct 15".parse().unwrap()));
+    assert_eq!(session.adjudicate(), Adjudication::Ongoing);
+    assert!(session.play(Move::Pass));
+    assert_eq!(session.adjudicate(), Adjudication::Draw(DrawReason::PlyLimit));
+    session.set_ply_limit(None);
+    assert_eq!(session.adjudicate(), Adjudication::Ongoing);
+
+    // Red ships alone under red stars, with nothing to attack on either side
+    let position = "white: r3 r1 | r2 |\nblack: r2 r1 | | r3\nturn: white";
+    let session = Session::from_game(Game::from_system_list(position).unwrap());
+    assert_eq!(session.result(), GameResult::Ongoing);
+    assert_eq!(session.adjudicate(), Adjudication::Draw(DrawReason::Stalemate));
+}
+
+#[test]
+fn test_ply_limit() {
+    let mut session = Session::new();
+    assert_eq!(session.ply_limit(), None);
+
+    // A limit of nothing draws the game before it starts
+    session.set_ply_limit(Some(0));
+    assert_eq!(session.ply_limit(), Some(0));
+    assert_eq!(session.adjudicate(), Adjudication::Draw(DrawReason::PlyLimit));
+    assert!(!session.play("select large green".parse().unwrap()));
+
+    // Homeworlds half built count for nothing once the limit is hit
+    let mut session = Session::new();
+    session.set_ply_limit(Some(3));
+    for m in ["select large green", "select small blue", "select large yellow"] {
+        assert_eq!(session.adjudicate(), Adjudication::Ongoing);
+        assert!(session.play(m.parse().unwrap()), "{}", m);
+    }
+    assert_eq!(session.adjudicate(), Adjudication::Draw(DrawReason::PlyLimit));
+    assert!(!session.play("select medium yellow".parse().unwrap()));
+    assert_eq!(session.game().ply(), 3);
+    assert_eq!(session.history().len(), 3);
+}
+
+#[test]
+fn test_move_filter() {
+    // Passing is only allowed to a player with nothing else to do
+    let filter: MoveFilter = Arc::new(|game: &Game, m| m != Move::Pass || !game.has_legal_action());
+    let pass = MOVES.iter().position(|&m| m == Move::Pass).unwrap();
+
+    let mut session = setup_session();
+    session.set_move_filter(Some(filter.clone()));
+    assert!(session.game().legal_moves().contains(&pass));
+    assert!(!session.legal_moves().contains(&pass));
+    assert!(!session.play(Move::Pass));
+    assert!(session.history().is_empty());
+    assert!(session.play("construct 15".parse().unwrap()));
+
+    // A player down to a pass still gets it
+    let position = "white: g3 b1 | y3 r1 |\nblack: y2 | | g2\nturn: white";
+    let mut session = Session::from_game(Game::from_system_list(position).unwrap());
+    session.set_move_filter(Some(filter));
+    assert!(session.play("sacrifice 0".parse().unwrap()));
+    assert_eq!(session.legal_moves(), vec![pass]);
+    assert!(session.play(Move::Pass));
+
+    // Removing the rule brings the pass back
+    let mut session = setup_session();
+    session.set_move_filter(None);
+    assert!(session.play(Move::Pass));
+}
+
+#[test]
+fn test_session_thread_safety() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Session>();
+    assert_send_sync::<Annotation>();
+    assert_send_sync::<Adjudication>();
+}
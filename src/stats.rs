@@ -0,0 +1,86 @@
+// Move tallies over a game, for studying how often each ability gets used
+use crate::game::{Color, Move, MoveKind};
+
+// Counts of moves by verb, and by color for the verbs that name one
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct MoveStats {
+    kinds: [u32; 8],  // Indexed in `MoveKind::list` order
+    colors: [u32; 4], // Colors named by transforms and homeworld selections
+}
+
+impl MoveStats {
+    // Constructor method to create an empty tally
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Method to count one move. Both halves of a movement count, as they do in the move table.
+    pub fn record(&mut self, m: Move) {
+        let kind = MoveKind::list().iter().position(|&k| k == m.kind()).unwrap();
+        self.kinds[kind] += 1;
+        match m {
+            Move::Transform(_, color) | Move::Select(_, color) => self.colors[color as usize] += 1,
+            _ => {}
+        }
+    }
+
+    // Method to count every move of a stream
+    pub fn record_all<I: IntoIterator<Item = Move>>(&mut self, moves: I) {
+        for m in moves {
+            self.record(m);
+        }
+    }
+
+    // Method to get how many moves of one verb were recorded
+    pub fn kind_count(&self, kind: MoveKind) -> u32 {
+        let i = MoveKind::list().iter().position(|&k| k == kind).unwrap();
+        self.kinds[i]
+    }
+
+    // Method to get how many transforms and selections named a color
+    pub fn color_count(&self, color: Color) -> u32 {
+        self.colors[color as usize]
+    }
+
+    // Method to get how many moves were recorded in all
+    pub fn total(&self) -> u32 {
+        self.kinds.iter().sum()
+    }
+}
+
+#[test]
+fn test_move_stats() {
+    let moves: Vec<Move> = [
+        "select large green",
+        "select small blue",
+        "select large yellow",
+        "select medium yellow",
+        "select medium green",
+        "select small yellow",
+        "construct 15",
+        "pass",
+        "transform 9 green",
+        "pass",
+        "moveinit 15",
+        "movefinish 12",
+    ]
+    .iter()
+    .map(|m| m.parse().unwrap())
+    .collect();
+    let mut stats = MoveStats::new();
+    stats.record_all(moves);
+
+    assert_eq!(stats.total(), 12);
+    assert_eq!(stats.kind_count(MoveKind::Select), 6);
+    assert_eq!(stats.kind_count(MoveKind::Construct), 1);
+    assert_eq!(stats.kind_count(MoveKind::Transform), 1);
+    assert_eq!(stats.kind_count(MoveKind::Pass), 2);
+    assert_eq!(stats.kind_count(MoveKind::Move), 2);
+    assert_eq!(stats.kind_count(MoveKind::Attack), 0);
+
+    // Only selections and transforms name a color
+    assert_eq!(stats.color_count(Color::Red), 0);
+    assert_eq!(stats.color_count(Color::Yellow), 3);
+    assert_eq!(stats.color_count(Color::Green), 3);
+    assert_eq!(stats.color_count(Color::Blue), 1);
+}
@@ -0,0 +1,238 @@
+// Retrograde analysis over the positions reachable from a starting game
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::game::{Game, GameResult, Player};
+
+// Solved value of a position from the perspective of the player to move.
+// Distances count moves (actions), so a sacrifice turn spans several of them.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameValue {
+    Win(u32),  // The player to move wins in this many moves
+    Loss(u32), // The player to move loses in this many moves
+    Draw,      // Neither player can force a win
+}
+
+// A position discovered during the forward search
+struct Node {
+    player: Player,
+    children: Vec<u64>,
+    expanded: bool,
+}
+
+// Function to solve every position reachable from `game` within `max_positions`
+// discovered positions. Positions whose value depends on unexplored positions are
+// left out of the result.
+pub fn solve(game: &Game, max_positions: usize) -> HashMap<u64, GameValue> {
+    let mut nodes: HashMap<u64, Node> = HashMap::new();
+    let mut values: HashMap<u64, GameValue> = HashMap::new();
+
+    // Forward pass: breadth-first discovery of the position graph
+    let mut queue = VecDeque::new();
+    let root = game.hash();
+    nodes.insert(
+        root,
+        Node {
+            player: game.to_move(),
+            children: Vec::new(),
+            expanded: false,
+        },
+    );
+    queue.push_back(game.clone());
+    while let Some(game) = queue.pop_front() {
+        let hash = game.hash();
+        match game.game_result() {
+            GameResult::Ongoing => {}
+            GameResult::Win(p) | GameResult::Resignation(p) if p == game.to_move() => {
+                values.insert(hash, GameValue::Win(0));
+                continue;
+            }
+            GameResult::Win(_) | GameResult::Resignation(_) => {
+                values.insert(hash, GameValue::Loss(0));
+                continue;
+            }
+            GameResult::Draw | GameResult::AgreedDraw => {
+                values.insert(hash, GameValue::Draw);
+                continue;
+            }
+        }
+        let mut children = Vec::new();
+        for i in game.legal_moves() {
+            let mut child = game.clone();
+            child.process_move_idx(i);
+            let chash = child.hash();
+            if !children.contains(&chash) {
+                children.push(chash);
+            }
+            if !nodes.contains_key(&chash) && nodes.len() < max_positions {
+                nodes.insert(
+                    chash,
+                    Node {
+                        player: child.to_move(),
+                        children: Vec::new(),
+                        expanded: false,
+                    },
+                );
+                queue.push_back(child);
+            }
+        }
+        let node = nodes.get_mut(&hash).unwrap();
+        node.children = children;
+        node.expanded = true;
+    }
+
+    // Backward pass: resolve one distance at a time. Each round only reads values from
+    // earlier rounds, so a position is first resolved at its shortest distance
+    loop {
+        let mut found = Vec::new();
+        for (&hash, node) in nodes.iter() {
+            if !node.expanded || values.contains_key(&hash) {
+                continue;
+            }
+            let mut best_win: Option<u32> = None;
+            let mut worst_loss: Option<u32> = Some(0);
+            for chash in &node.children {
+                // Translate the child's value into this node's perspective
+                let same = nodes.get(chash).map(|c| c.player == node.player);
+                let value = match (values.get(chash), same) {
+                    (Some(&v), Some(true)) => Some(v),
+                    (Some(&GameValue::Win(n)), Some(false)) => Some(GameValue::Loss(n)),
+                    (Some(&GameValue::Loss(n)), Some(false)) => Some(GameValue::Win(n)),
+                    (Some(&GameValue::Draw), _) => Some(GameValue::Draw),
+                    _ => None,
+                };
+                match value {
+                    Some(GameValue::Win(n)) => {
+                        best_win = Some(best_win.map_or(n + 1, |b| b.min(n + 1)));
+                    }
+                    Some(GameValue::Loss(n)) => {
+                        worst_loss = worst_loss.map(|w| w.max(n + 1));
+                    }
+                    _ => worst_loss = None,
+                }
+            }
+            let value = match (best_win, worst_loss) {
+                (Some(n), _) => GameValue::Win(n),
+                (None, Some(n)) if !node.children.is_empty() => GameValue::Loss(n),
+                _ => continue,
+            };
+            found.push((hash, value));
+        }
+        if found.is_empty() {
+            break;
+        }
+        values.extend(found);
+    }
+
+    // Unresolved positions that can reach an unexplored one have unknown values
+    let mut unknown: HashSet<u64> = nodes
+        .iter()
+        .filter(|(hash, node)| !node.expanded && !values.contains_key(hash))
+        .map(|(&hash, _)| hash)
+        .collect();
+    loop {
+        let more: Vec<u64> = nodes
+            .iter()
+            .filter(|(hash, node)| {
+                !values.contains_key(hash)
+                    && !unknown.contains(hash)
+                    && node
+                        .children
+                        .iter()
+                        .any(|c| unknown.contains(c) || !nodes.contains_key(c))
+            })
+            .map(|(&hash, _)| hash)
+            .collect();
+        if more.is_empty() {
+            break;
+        }
+        unknown.extend(more);
+    }
+
+    // The rest can only cycle forever
+    for &hash in nodes.keys() {
+        if !unknown.contains(&hash) {
+            values.entry(hash).or_insert(GameValue::Draw);
+        }
+    }
+    values
+}
+
+#[test]
+fn test_solve_win_in_one() {
+    let mut game = Game::new();
+    for m in [
+        // White: large green + small blue homeworld, large yellow ship
+        "select large green",
+        "select small blue",
+        "select large yellow",
+        // Black: medium red + medium green homeworld, small blue ship
+        "select medium red",
+        "select medium green",
+        "select small blue",
+        // White builds a second ship and sends the large one to Black's homeworld
+        "construct 15",
+        "pass",
+        "moveinit 15",
+        "movefinish 3",
+        "pass",
+    ] {
+        assert!(game.process_move(m.parse().unwrap()), "{}", m);
+    }
+
+    // Capturing Black's only ship leaves their homeworld empty
+    let values = solve(&game, 2000);
+    assert_eq!(values.get(&game.hash()), Some(&GameValue::Win(1)));
+}
+
+#[test]
+fn test_solve_shortest_win() {
+    // White can capture Black's last ship straight away, or first sacrifice a ship for
+    // the attack
+    let game = Game::from_system_list(
+        "white: r2 b1 | g2 |\nblack: y2 b2 | r3 r1 | g1\nturn: white",
+    )
+    .unwrap();
+    let values = solve(&game, 2000);
+    assert_eq!(values.get(&game.hash()), Some(&GameValue::Win(1)));
+    let mut sacrificed = game.clone();
+    assert!(sacrificed.process_move("sacrifice 6".parse().unwrap()));
+    assert_eq!(values.get(&sacrificed.hash()), Some(&GameValue::Win(2)));
+
+    // Every solved position must take its shortest win, or put off its loss the longest
+    let mut seen = HashSet::new();
+    let mut stack = vec![game];
+    while let Some(game) = stack.pop() {
+        if !seen.insert(game.hash()) {
+            continue;
+        }
+        let value = match values.get(&game.hash()) {
+            Some(&GameValue::Win(n)) if n > 0 => GameValue::Win(n),
+            Some(&GameValue::Loss(n)) if n > 0 => GameValue::Loss(n),
+            _ => continue,
+        };
+        let mut wins = Vec::new();
+        let mut losses = Vec::new();
+        for i in game.legal_moves() {
+            let mut child = game.clone();
+            child.process_move_idx(i);
+            let same = child.to_move() == game.to_move();
+            match (values.get(&child.hash()), same) {
+                (Some(&GameValue::Win(n)), true) | (Some(&GameValue::Loss(n)), false) => {
+                    wins.push(n + 1)
+                }
+                (Some(&GameValue::Loss(n)), true) | (Some(&GameValue::Win(n)), false) => {
+                    losses.push(n + 1)
+                }
+                _ => {}
+            }
+            stack.push(child);
+        }
+        match value {
+            GameValue::Win(n) => assert_eq!(wins.iter().min(), Some(&n)),
+            _ => assert_eq!(wins.len(), 0),
+        }
+        if let GameValue::Loss(n) = value {
+            assert_eq!(losses.iter().max(), Some(&n));
+        }
+    }
+}
@@ -0,0 +1,19 @@
+// 128bit multiply function
+fn wymum(a: u64, b: u64) -> (u64, u64) {
+    let r = (a as u128) * (b as u128);
+    (r as u64, (r >> 64) as u64)
+}
+
+// multiply and xor mix function, aka MUM
+fn wymix(a: u64, b: u64) -> u64 {
+    let (a, b) = wymum(a, b);
+    a ^ b
+}
+
+// wyhash64 function
+pub(crate) fn wyhash64(a: u64, b: u64) -> u64 {
+    let a = a ^ 0x2d358dccaa6c78a5;
+    let b = b ^ 0x8bb84b93962eacc9;
+    let (a, b) = wymum(a, b);
+    wymix(a ^ 0x2d358dccaa6c78a5, b ^ 0x8bb84b93962eacc9)
+}
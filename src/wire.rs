@@ -0,0 +1,244 @@
+// The two-byte move encoding behind `game::encode_move` and `game::decode_move`
+
+#[derive(Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Debug)]
+#[repr(u8)]
+pub(crate) enum Size {
+    Small = 0,
+    Medium = 1,
+    Large = 2,
+}
+
+impl Size {
+    pub(crate) const fn from_u8(v: u8) -> Option<Size> {
+        match v {
+            0 => Some(Size::Small),
+            1 => Some(Size::Medium),
+            2 => Some(Size::Large),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub(crate) enum Color {
+    Red = 0 << 4,
+    Yellow = 1 << 4,
+    Green = 2 << 4,
+    Blue = 3 << 4,
+}
+
+impl Color {
+    pub(crate) const fn from_u8(v: u8) -> Option<Color> {
+        match v {
+            0x00 => Some(Color::Red),
+            0x10 => Some(Color::Yellow),
+            0x20 => Some(Color::Green),
+            0x30 => Some(Color::Blue),
+            _ => None,
+        }
+    }
+
+    // Colors in the order red, yellow, green, blue, unshifted
+    pub(crate) const fn from_index(i: u8) -> Option<Color> {
+        Self::from_u8(i << 4)
+    }
+
+    pub(crate) const fn index(self) -> u8 {
+        self as u8 >> 4
+    }
+}
+
+type Key = u8;
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub(crate) enum MoveData {
+    Attack { piece: Key },
+    Move { piece: Key, system: Key },
+    Construct { piece: Key },
+    Transform { piece: Key, color: Color },
+    Sacrifice { piece: Key },
+    Select { size: Size, color: Color },
+    Catastrophe { piece: Key },
+    Pass
+}
+
+
+pub struct Move(u16);
+impl Move {
+    const B3_MASK: u16 = 0b1110_0000_0000_0000;
+    const B6_MASK: u16 = 0b0001_1111_1000_0000;
+    const B7_MASK: u16 = 0b0000_0000_0111_1111;
+
+    const B3_SHIFT: u16 = 13;
+    const B6_SHIFT: u16 = 7;
+
+    pub(crate) fn new(data: MoveData) -> Self {
+        Self(match data {
+            MoveData::Attack { piece } => {
+                (0b000 << Self::B3_SHIFT) | (piece as u16)
+            }
+            MoveData::Move { piece, system } => {
+                (0b001 << Self::B3_SHIFT)
+                    | ((piece as u16) << Self::B6_SHIFT)
+                    | (system as u16)
+            }
+            MoveData::Construct { piece } => {
+                (0b010 << Self::B3_SHIFT) | (piece as u16)
+            }
+            MoveData::Transform { piece, color } => {
+                (0b011 << Self::B3_SHIFT)
+                    | ((piece as u16) << Self::B6_SHIFT)
+                    | (color as u16)
+            }
+            MoveData::Sacrifice { piece } => {
+                (0b100 << Self::B3_SHIFT) | (piece as u16)
+            }
+            MoveData::Select { size, color } => {
+                (0b101 << Self::B3_SHIFT)
+                    | ((size as u16) << Self::B6_SHIFT)
+                    | (color as u16)
+            }
+            MoveData::Catastrophe { piece } => {
+                (0b110 << Self::B3_SHIFT) | (piece as u16)
+            }
+            MoveData::Pass => 0b111 << Self::B3_SHIFT,
+        })
+    }
+
+    fn data(self) -> MoveData {
+        let b3 = ((self.0 & Self::B3_MASK) >> Self::B3_SHIFT) as u8;
+        let b6 = ((self.0 & Self::B6_MASK) >> Self::B6_SHIFT) as u8;
+        let b7 = (self.0 & Self::B7_MASK) as u8;
+    
+        match b3 {
+            0b000 => MoveData::Attack { piece: b7 },
+            0b001 => MoveData::Move {
+                piece: b6,
+                system: b7,
+            },
+            0b010 => MoveData::Construct {
+                piece: b7,
+            },
+            0b011 => MoveData::Transform {
+                piece: b6,
+                color: unsafe {
+                    std::mem::transmute::<u8, Color>(b7)
+                },
+            },
+            0b100 => MoveData::Sacrifice {
+                piece: b7,
+            },
+            0b101 => MoveData::Select {
+                size: unsafe {
+                    std::mem::transmute::<u8, Size>(b6)
+                },
+                color: unsafe {
+                    std::mem::transmute::<u8, Color>(b7)
+                },
+            },
+            0b110 => MoveData::Catastrophe {
+                piece: b7,
+            },
+            0b111 => MoveData::Pass,
+            _ => unreachable!(),
+        }
+    }
+
+    pub(crate) const fn bits(self) -> u16 {
+        self.0
+    }
+
+    // Decode raw bits from outside the engine, rejecting colors and sizes that don't
+    // exist and set bits that the verb doesn't use. Key ranges are left to the caller.
+    pub(crate) fn try_from_bits(bits: u16) -> Option<MoveData> {
+        let b3 = ((bits & Self::B3_MASK) >> Self::B3_SHIFT) as u8;
+        let b6 = ((bits & Self::B6_MASK) >> Self::B6_SHIFT) as u8;
+        let b7 = (bits & Self::B7_MASK) as u8;
+        let valid = match b3 {
+            0b000 | 0b010 | 0b100 | 0b110 => b6 == 0,
+            0b001 => true,
+            0b011 => Color::from_u8(b7).is_some(),
+            0b101 => Size::from_u8(b6).is_some() && Color::from_u8(b7).is_some(),
+            _ => b6 == 0 && b7 == 0,
+        };
+        valid.then(|| Self(bits).data())
+    }
+}
+
+#[test]
+fn test_move_attack() {
+    let move_data = MoveData::Attack { piece: 1 };
+    let mv = Move::new(move_data);
+    assert_eq!(mv.0, 0b0000_0000_0000_0001u16);
+    assert_eq!(mv.data(), move_data);
+}
+
+#[test]
+fn test_move_move() {
+    let move_data = MoveData::Move { piece: 2, system: 3 };
+    let mv = Move::new(move_data);
+    assert_eq!(mv.0, 0b0010_0001_0000_0011u16);
+    assert_eq!(mv.data(), move_data);
+}
+
+#[test]
+fn test_move_construct() {
+    let move_data = MoveData::Construct { piece: 4 };
+    let mv = Move::new(move_data);
+    assert_eq!(mv.0, 0b0100_0000_0000_0100u16);
+    assert_eq!(mv.data(), move_data);
+}
+
+#[test]
+fn test_move_transform() {
+    let move_data = MoveData::Transform { piece: 5, color: Color::Red };
+    let mv = Move::new(move_data);
+    assert_eq!(mv.0, 0b0110_0010_1000_0000u16);
+    assert_eq!(mv.data(), move_data);
+}
+
+#[test]
+fn test_move_sacrifice() {
+    let move_data = MoveData::Sacrifice { piece: 6 };
+    let mv = Move::new(move_data);
+    assert_eq!(mv.0, 0b1000_0000_0000_0110u16);
+    assert_eq!(mv.data(), move_data);
+}
+
+#[test]
+fn test_move_select() {
+    let move_data = MoveData::Select { size: Size::Small, color: Color::Blue };
+    let mv = Move::new(move_data);
+    assert_eq!(mv.0, 0b1010_0000_0011_0000u16);
+    assert_eq!(mv.data(), move_data);
+}
+
+#[test]
+fn test_move_catastrophe() {
+    let move_data = MoveData::Catastrophe { piece: 8 };
+    let mv = Move::new(move_data);
+    assert_eq!(mv.0, 0b1100_0000_0000_1000u16);
+    assert_eq!(mv.data(), move_data);
+}
+
+#[test]
+fn test_move_pass() {
+    let move_data = MoveData::Pass;
+    let mv = Move::new(move_data);
+    assert_eq!(mv.0, 0b1110_0000_0000_0000u16);
+    assert_eq!(mv.data(), move_data);
+}
+
+#[test]
+fn test_move_try_from_bits() {
+    let move_data = MoveData::Select { size: Size::Large, color: Color::Green };
+    assert_eq!(Move::try_from_bits(Move::new(move_data).bits()), Some(move_data));
+
+    // A size of 3 and a color with its low bits set don't exist
+    assert_eq!(Move::try_from_bits(0b1010_0001_1001_0000), None);
+    assert_eq!(Move::try_from_bits(0b0110_0010_1000_0001), None);
+    // Single-piece verbs and passes carry no other fields
+    assert_eq!(Move::try_from_bits(0b0000_0000_1000_0001), None);
+    assert_eq!(Move::try_from_bits(0b1110_0000_0000_0001), None);
+}